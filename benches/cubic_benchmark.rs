@@ -1,3 +1,6 @@
+// Experiments comparing root finders, kept as they were written.
+#![allow(clippy::needless_return, clippy::manual_clamp)]
+
 use std::f32::consts::PI;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
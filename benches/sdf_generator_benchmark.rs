@@ -1,16 +1,38 @@
-use std::f32::consts::PI;
-
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use msdfont::{Font, Offset, Scale};
 
 fn sdf_benchmark(crit: &mut Criterion) {
-    // Cubic constants
-    let a = black_box(1.0);
-    let b = black_box(100.4);
-    let c = black_box(-100.4);
-    let d = black_box(-0.29);
-    crit.bench_function("default overlap correction", |bencher| {
-        bencher.iter(|| todo!())
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let glyph = font
+        .glyph('@')
+        .build(Scale::uniform(64.0), Offset::uniform(4.0))
+        .unwrap();
+
+    crit.bench_function("sdf generation", |bencher| {
+        bencher.iter(|| glyph.generate_sdf(black_box(8)))
     });
+    crit.bench_function("msdf generation", |bencher| {
+        bencher.iter(|| glyph.generate_msdf(black_box(8)))
+    });
+
+    // Run with `--features parallel`. The serial variant runs the same
+    // code on a single thread to show the speedup.
+    #[cfg(feature = "parallel")]
+    {
+        let single_thread = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        crit.bench_function("msdf generation (serial)", |bencher| {
+            bencher.iter(|| {
+                single_thread.install(|| glyph.generate_msdf(black_box(8)))
+            })
+        });
+        crit.bench_function("msdf generation (parallel)", |bencher| {
+            bencher.iter(|| glyph.generate_msdf(black_box(8)))
+        });
+    }
 }
 
 criterion_group!(benches, sdf_benchmark);
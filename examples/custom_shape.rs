@@ -1,7 +1,10 @@
-use image::{DynamicImage, GenericImage, Rgba};
-use msdfont::{GlyphOutline, Offset, Scale, ShapeBuilder};
+use msdfont::{
+    FieldType, GeneratorConfig, GlyphOutline, Offset, Scale, ShapeBuilder,
+    YAxis,
+};
 
 fn main() {
+    // The shape is drawn with `y` increasing upwards.
     let mut builder = ShapeBuilder::new(
         10,
         10,
@@ -12,28 +15,21 @@ fn main() {
     builder.line_to(10.0, 5.0);
     builder.line_to(10.0, 0.0);
     builder.line_to(-5.0, 5.0);
-    builder.close();
+    builder.close().unwrap();
     builder.open_at(0.0, 0.0);
     builder.quad_to(1.0, 9.0, 9.0, 9.0);
-    builder.close();
+    builder.close().unwrap();
 
-    let (shape, bbox) = builder.build();
+    let (shape, bbox) = builder.build().unwrap();
 
     let glyph = GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
 
-    let width = glyph.width() as u32;
-    let height = glyph.height() as u32;
+    // The drawing is y-up so the first row has to be its top.
+    let config = GeneratorConfig::new()
+        .field_type(FieldType::Sdf)
+        .range(6)
+        .y_axis(YAxis::Up);
+    let sdf = glyph.generate(&config).into_u8().unwrap();
 
-    let sdf = glyph.generate_sdf(6).data();
-
-    let mut image = DynamicImage::new_rgb8(width, height);
-
-    for y in 0..height {
-        for x in 0..width {
-            let pixel = sdf[((height - y - 1) * width + x) as usize];
-            image.put_pixel(x, y, Rgba([pixel, pixel, pixel, 255]));
-        }
-    }
-
-    image.save("examples/test.png").unwrap();
+    sdf.save_png("examples/test.png").unwrap();
 }
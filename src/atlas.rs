@@ -0,0 +1,508 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::{GeneratorConfig, YAxis},
+    error::{Error, Result},
+    font::{Font, Offset},
+    gen::{Bitmap, FieldType},
+    range::Range,
+    vector::Vector2,
+};
+use rusttype::Scale;
+
+/// Settings used for generating all glyphs of an [`Atlas`].
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasSettings {
+    /// Type of the distance field generated for each glyph.
+    pub field_type: FieldType,
+    /// Scale at which glyphs are built.
+    pub scale: Scale,
+    /// Distance range, ranges in ems are relative to the `scale`.
+    pub range: Range,
+    /// Empty space around each glyph inside its bitmap where the
+    /// distance field fades out.
+    pub offset: Offset,
+    /// Empty space in pixels between packed glyph bitmaps and around
+    /// the page edges.
+    pub padding: u32,
+    /// Width of each page in pixels.
+    pub page_width: u32,
+    /// Height of each page in pixels.
+    pub page_height: u32,
+    /// Direction in which the rows of pages go. With [`YAxis::Up`] the
+    /// pages are flipped and uv coordinates start at the bottom.
+    pub y_axis: YAxis,
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        Self {
+            field_type: FieldType::Msdf,
+            scale: Scale::uniform(32.0),
+            range: Range::pixels(4.0),
+            offset: Offset::uniform(2.0),
+            padding: 1,
+            page_width: 512,
+            page_height: 512,
+            y_axis: YAxis::Down,
+        }
+    }
+}
+
+impl AtlasSettings {
+    /// Returns the config every glyph is generated with: the default
+    /// [`GeneratorConfig`] with the field type and range of the settings.
+    ///
+    /// Rows always go down since whole pages get flipped for
+    /// [`YAxis::Up`].
+    pub fn generator_config(&self) -> GeneratorConfig {
+        GeneratorConfig::new()
+            .field_type(self.field_type)
+            .range(self.range)
+    }
+}
+
+/// Rectangle on an atlas page in pixels, with `y` increasing downwards.
+///
+/// Relative to the first row of the page data, which is the top of the
+/// page saved as an image. With [`YAxis::Up`] the glyph in the rectangle
+/// is upside down since the pages are flipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Rectangle on an atlas page in normalized texture coordinates where
+/// `(0, 0)` is the first pixel of the page data.
+///
+/// With [`YAxis::Up`] the glyph top is above its bottom so `min.y` is
+/// bigger than `max.y`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    /// Top left corner of the glyph.
+    pub min: Vector2<f32>,
+    /// Bottom right corner of the glyph.
+    pub max: Vector2<f32>,
+}
+
+/// Bounds of the glyph bitmap in pixels relative to the glyph origin
+/// (the point on the baseline where the pen is placed), with `y`
+/// increasing downwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaneBounds {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+/// Where the glyph bitmap is placed in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphPlacement {
+    /// Index of the page.
+    pub page: usize,
+    pub rect: AtlasRect,
+    pub uv: UvRect,
+    pub plane_bounds: PlaneBounds,
+}
+
+/// A single glyph of the [`Atlas`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasGlyph {
+    pub character: char,
+    /// The glyph identifier in the font.
+    pub id: u16,
+    /// Horizontal advance in pixels.
+    pub advance: f32,
+    /// `None` for glyphs without an outline, like space.
+    pub placement: Option<GlyphPlacement>,
+}
+
+/// Distance field bitmaps of multiple glyphs packed into one or more pages.
+pub struct Atlas {
+    pub pages: Vec<Bitmap>,
+    pub glyphs: Vec<AtlasGlyph>,
+    pub settings: AtlasSettings,
+}
+
+impl Atlas {
+    /// Generates distance fields for every character of the charset and
+    /// packs them into pages.
+    ///
+    /// Characters which the font has no glyph for are skipped, as well as
+    /// repeated characters. Returns [`Error::AtlasTooSmall`] if a glyph
+    /// doesn't fit into an empty page.
+    pub fn generate<I>(
+        font: &Font,
+        charset: I,
+        settings: AtlasSettings,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut seen = HashSet::new();
+        let mut chars = Vec::new();
+        for c in charset {
+            if seen.insert(c) && font.try_glyph(c).is_ok() {
+                chars.push(c);
+            }
+        }
+
+        // Glyphs are collected in the charset order either way, so the
+        // packing doesn't depend on the `parallel` feature.
+        #[cfg(feature = "parallel")]
+        let generated = {
+            use rayon::prelude::*;
+            chars
+                .into_par_iter()
+                .map(|c| generate_glyph(font, c, &settings))
+                .collect::<Result<Vec<_>>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let generated = chars
+            .into_iter()
+            .map(|c| generate_glyph(font, c, &settings))
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::pack(generated, settings)
+    }
+
+    /// Packs already generated glyph bitmaps into pages.
+    fn pack(
+        generated: Vec<(AtlasGlyph, Bitmap, Vector2<f32>)>,
+        settings: AtlasSettings,
+    ) -> Result<Self> {
+        let channels = settings.field_type.channels();
+        let (page_width, page_height) =
+            (settings.page_width, settings.page_height);
+
+        // Taller glyphs first leave less wasted space under the skyline.
+        let mut order = (0..generated.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(generated[i].1.height));
+
+        let mut pages: Vec<Bitmap> = Vec::new();
+        let mut skylines: Vec<Skyline> = Vec::new();
+        let mut glyphs = generated.iter().map(|g| g.0).collect::<Vec<_>>();
+
+        for i in order {
+            let (_, bitmap, origin) = &generated[i];
+            if bitmap.width == 0 || bitmap.height == 0 {
+                continue;
+            }
+
+            let mut placed = None;
+            for (page, skyline) in skylines.iter_mut().enumerate() {
+                if let Some(pos) = skyline.insert(bitmap.width, bitmap.height) {
+                    placed = Some((page, pos));
+                    break;
+                }
+            }
+            let (page, (x, y)) = match placed {
+                Some(placed) => placed,
+                None => {
+                    let mut skyline =
+                        Skyline::new(page_width, page_height, settings.padding);
+                    let pos = skyline
+                        .insert(bitmap.width, bitmap.height)
+                        .ok_or(Error::AtlasTooSmall)?;
+                    skylines.push(skyline);
+                    pages.push(Bitmap {
+                        data: vec![
+                            0;
+                            (page_width * page_height * channels)
+                                as usize
+                        ],
+                        width: page_width,
+                        height: page_height,
+                        channels,
+                    });
+                    (pages.len() - 1, pos)
+                }
+            };
+
+            copy_bitmap(&mut pages[page], bitmap, x, y);
+
+            let mut rect = AtlasRect {
+                x,
+                y,
+                width: bitmap.width,
+                height: bitmap.height,
+            };
+            if settings.y_axis == YAxis::Up {
+                rect.y = page_height - y - bitmap.height;
+            }
+            let mut uv = UvRect {
+                min: Vector2::new(
+                    x as f32 / page_width as f32,
+                    y as f32 / page_height as f32,
+                ),
+                max: Vector2::new(
+                    (x + bitmap.width) as f32 / page_width as f32,
+                    (y + bitmap.height) as f32 / page_height as f32,
+                ),
+            };
+            if settings.y_axis == YAxis::Up {
+                uv.min.y = 1.0 - uv.min.y;
+                uv.max.y = 1.0 - uv.max.y;
+            }
+            let plane_bounds = PlaneBounds {
+                left: -origin.x,
+                top: -origin.y,
+                right: bitmap.width as f32 - origin.x,
+                bottom: bitmap.height as f32 - origin.y,
+            };
+            glyphs[i].placement = Some(GlyphPlacement {
+                page,
+                rect,
+                uv,
+                plane_bounds,
+            });
+        }
+
+        if settings.y_axis == YAxis::Up {
+            pages.iter_mut().for_each(Bitmap::flip_y);
+        }
+
+        Ok(Self {
+            pages,
+            glyphs,
+            settings,
+        })
+    }
+
+    /// Returns the atlas glyph of the provided character.
+    pub fn glyph(&self, c: char) -> Option<&AtlasGlyph> {
+        self.glyphs.iter().find(|glyph| glyph.character == c)
+    }
+}
+
+fn generate_glyph(
+    font: &Font,
+    c: char,
+    settings: &AtlasSettings,
+) -> Result<(AtlasGlyph, Bitmap, Vector2<f32>)> {
+    let glyph = font.glyph(c);
+    let id = glyph.id();
+    let outline = glyph.build(settings.scale, settings.offset)?;
+    // The default format is `u8`.
+    let bitmap = outline
+        .generate(&settings.generator_config())
+        .into_u8()
+        .unwrap();
+
+    let atlas_glyph = AtlasGlyph {
+        character: c,
+        id,
+        advance: outline.h_metrics().advance_width,
+        placement: None,
+    };
+    Ok((atlas_glyph, bitmap, outline.origin()))
+}
+
+/// Copies the `src` bitmap into `dst` with the top left corner at `x`, `y`.
+fn copy_bitmap(dst: &mut Bitmap, src: &Bitmap, x: u32, y: u32) {
+    let channels = src.channels as usize;
+    let row_len = src.width as usize * channels;
+    for row in 0..src.height as usize {
+        let src_start = row * row_len;
+        let dst_start =
+            ((y as usize + row) * dst.width as usize + x as usize) * channels;
+        dst.data[dst_start..dst_start + row_len]
+            .copy_from_slice(&src.data[src_start..src_start + row_len]);
+    }
+}
+
+/// Bottom-left skyline rectangle packer.
+///
+/// The skyline is a list of horizontal segments which represent the
+/// lowest free height for each part of the page width. Each rectangle
+/// is placed at the position where its top ends up the highest.
+struct Skyline {
+    width: u32,
+    height: u32,
+    padding: u32,
+    /// Segments `(x, y, width)` sorted by `x`.
+    nodes: Vec<(u32, u32, u32)>,
+}
+
+impl Skyline {
+    fn new(width: u32, height: u32, padding: u32) -> Self {
+        Self {
+            width,
+            height,
+            padding,
+            nodes: vec![(padding, padding, width.saturating_sub(padding))],
+        }
+    }
+
+    /// Finds a place for the rectangle and returns its top left corner.
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        // Each rectangle reserves padding on its right and bottom side.
+        let width = width + self.padding;
+        let height = height + self.padding;
+
+        let mut best: Option<(usize, u32)> = None;
+        for i in 0..self.nodes.len() {
+            let y = match self.fit(i, width, height) {
+                Some(y) => y,
+                None => continue,
+            };
+            if best.map_or(true, |(_, best_y)| y < best_y) {
+                best = Some((i, y));
+            }
+        }
+        let (index, y) = best?;
+        let x = self.nodes[index].0;
+
+        self.nodes.insert(index, (x, y + height, width));
+
+        // Shrink or remove the following nodes covered by the new one.
+        let right = x + width;
+        let i = index + 1;
+        while i < self.nodes.len() {
+            let (node_x, node_y, node_width) = self.nodes[i];
+            if node_x >= right {
+                break;
+            }
+            let node_right = node_x + node_width;
+            if node_right <= right {
+                self.nodes.remove(i);
+            } else {
+                self.nodes[i] = (right, node_y, node_right - right);
+                break;
+            }
+        }
+
+        // Merge neighbors of the same height.
+        let mut i = 0;
+        while i + 1 < self.nodes.len() {
+            if self.nodes[i].1 == self.nodes[i + 1].1 {
+                self.nodes[i].2 += self.nodes[i + 1].2;
+                self.nodes.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+
+        Some((x, y))
+    }
+
+    /// Returns the `y` at which the rectangle fits if placed at the
+    /// start of the node `index`.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.nodes[index].0;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut remaining = width as i64;
+        for &(_, node_y, node_width) in &self.nodes[index..] {
+            if remaining <= 0 {
+                break;
+            }
+            y = y.max(node_y);
+            remaining -= node_width as i64;
+        }
+
+        if y + height > self.height {
+            return None;
+        }
+        Some(y)
+    }
+}
+
+#[test]
+fn skyline_test() {
+    let mut skyline = Skyline::new(10, 10, 1);
+    let mut rects = Vec::new();
+    while let Some((x, y)) = skyline.insert(3, 2) {
+        rects.push((x, y));
+    }
+
+    // 2 columns and 3 rows of 3x2 rectangles with 1 pixel padding.
+    assert_eq!(rects.len(), 6);
+    // Sizes including the padding.
+    let (w, h) = (4, 3);
+    for (i, a) in rects.iter().enumerate() {
+        assert!(a.0 >= 1 && a.0 + w <= 10);
+        assert!(a.1 >= 1 && a.1 + h <= 10);
+        for b in &rects[i + 1..] {
+            let apart_x = a.0 + w <= b.0 || b.0 + w <= a.0;
+            let apart_y = a.1 + h <= b.1 || b.1 + h <= a.1;
+            assert!(apart_x || apart_y);
+        }
+    }
+}
+
+#[test]
+fn atlas_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let settings = AtlasSettings {
+        page_width: 40,
+        page_height: 40,
+        ..Default::default()
+    };
+
+    let atlas = Atlas::generate(&font, "ABC DEFA".chars(), settings).unwrap();
+    assert_eq!(atlas.glyphs.len(), 7);
+    assert!(atlas.pages.len() > 1);
+    assert!(atlas.glyph(' ').unwrap().placement.is_none());
+
+    let a = atlas.glyph('A').unwrap();
+    let placement = a.placement.unwrap();
+    assert!(a.advance > 0.0);
+    assert!(placement.plane_bounds.top < 0.0);
+    assert!(placement.uv.max.x <= 1.0 && placement.uv.max.y <= 1.0);
+    assert_eq!(atlas.pages[placement.page].channels, 3);
+
+    // Rows of the glyph in the page.
+    let glyph_rows = |atlas: &Atlas, placement: GlyphPlacement| {
+        let page = &atlas.pages[placement.page];
+        let rect = placement.rect;
+        let row_len = (rect.width * 3) as usize;
+        (rect.y..rect.y + rect.height)
+            .map(|y| {
+                let start = ((y * page.width + rect.x) * 3) as usize;
+                page.data[start..start + row_len].to_vec()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Glyph bitmaps are the same as generated by the library.
+    let outline = font
+        .glyph('A')
+        .build(settings.scale, settings.offset)
+        .unwrap();
+    let expected = outline.generate(&GeneratorConfig::new()).into_u8().unwrap();
+    let expected_rows = expected
+        .data
+        .chunks_exact((expected.width * 3) as usize)
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+    assert_eq!(glyph_rows(&atlas, placement), expected_rows);
+
+    // Pages are flipped and uv coordinates start at the bottom.
+    let up_settings = AtlasSettings {
+        y_axis: YAxis::Up,
+        ..settings
+    };
+    let up = Atlas::generate(&font, "ABC DEFA".chars(), up_settings).unwrap();
+    let up_placement = up.glyph('A').unwrap().placement.unwrap();
+    assert_eq!(up_placement.uv.min.y, 1.0 - placement.uv.min.y);
+    // The rect points at the flipped glyph in the page data.
+    let mut flipped_rows = expected_rows.clone();
+    flipped_rows.reverse();
+    assert_eq!(glyph_rows(&up, up_placement), flipped_rows);
+    assert_eq!(
+        up_placement.uv.max.y,
+        up_placement.rect.y as f32 / settings.page_height as f32
+    );
+    let mut page = up.pages[placement.page].clone();
+    page.flip_y();
+    assert_eq!(page, atlas.pages[placement.page]);
+}
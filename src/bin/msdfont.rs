@@ -0,0 +1,310 @@
+//! Command-line tool for generating distance fields of glyphs and atlases.
+//!
+//! Run `msdfont --help` for the usage.
+
+use std::{fs, process};
+
+use msdfont::{
+    Atlas, AtlasSettings, Bitmap, FieldType, Font, Offset, Range, Scale, YAxis,
+};
+
+const USAGE: &str = "\
+Generates signed distance fields of font glyphs.
+
+USAGE:
+    msdfont <FONT> [OPTIONS]
+
+A single character is written into <OUTPUT>.png with its metrics in
+<OUTPUT>.json. Multiple characters are packed into an atlas written into
+<OUTPUT>_<PAGE>.png pages and an <OUTPUT>.json layout in the format of
+msdf-atlas-gen.
+
+OPTIONS:
+    -f, --face <INDEX>        Face of a font collection (.ttc, .otc)
+                              [default: 0]
+    -c, --char <CHAR>         Character or codepoint like U+0041 [default: A]
+        --codepoints <A-B>    Inclusive range of codepoints like 0x20-0x7e
+        --charset <FILE>      UTF-8 text file with the characters
+    -m, --mode <MODE>         sdf, psdf, msdf or mtsdf [default: msdf]
+    -s, --size <PX>           Font size in pixels [default: 32]
+    -r, --range <RANGE>       Distance range in pixels or ems with the `em`
+                              suffix like 0.125em [default: 4]
+    -p, --padding <PX>        Empty space around each glyph [default: 2]
+        --atlas-size <WxH>    Size of atlas pages [default: 512x512]
+        --y-up                Rows of images go from the bottom
+    -o, --output <OUTPUT>     Output path without extension [default: out]
+    -h, --help                Prints this message";
+
+struct Args {
+    font: String,
+    face: u32,
+    chars: Vec<char>,
+    field_type: FieldType,
+    size: f32,
+    range: Range,
+    padding: f32,
+    atlas_size: (u32, u32),
+    y_axis: YAxis,
+    output: String,
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(err) = run(&args) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Returns `None` if the help was requested.
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        font: String::new(),
+        face: 0,
+        chars: Vec::new(),
+        field_type: FieldType::Msdf,
+        size: 32.0,
+        range: Range::pixels(4.0),
+        padding: 2.0,
+        atlas_size: (512, 512),
+        y_axis: YAxis::Down,
+        output: "out".to_owned(),
+    };
+    let mut font = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for `{}`", arg))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-f" | "--face" => parsed.face = parse_number(&value()?)?,
+            "-c" | "--char" => parsed.chars.push(parse_char(&value()?)?),
+            "--codepoints" => parsed.chars.extend(parse_codepoints(&value()?)?),
+            "--charset" => {
+                let path = value()?;
+                let text = fs::read_to_string(&path)
+                    .map_err(|err| format!("can't read {}: {}", path, err))?;
+                parsed
+                    .chars
+                    .extend(text.chars().filter(|c| !c.is_control()));
+            }
+            "-m" | "--mode" => {
+                parsed.field_type = match value()?.as_str() {
+                    "sdf" => FieldType::Sdf,
+                    "psdf" => FieldType::PseudoSdf,
+                    "msdf" => FieldType::Msdf,
+                    "mtsdf" => FieldType::Mtsdf,
+                    mode => return Err(format!("unknown mode `{}`", mode)),
+                }
+            }
+            "-s" | "--size" => parsed.size = parse_number(&value()?)?,
+            "-r" | "--range" => {
+                let range = value()?;
+                parsed.range = match range.strip_suffix("em") {
+                    Some(ems) => Range::ems(parse_number(ems)?),
+                    None => Range::pixels(parse_number(&range)?),
+                };
+            }
+            "-p" | "--padding" => parsed.padding = parse_number(&value()?)?,
+            "--atlas-size" => {
+                let size = value()?;
+                let (width, height) = size
+                    .split_once('x')
+                    .ok_or_else(|| format!("invalid atlas size `{}`", size))?;
+                parsed.atlas_size =
+                    (parse_number(width)?, parse_number(height)?);
+            }
+            "--y-up" => parsed.y_axis = YAxis::Up,
+            "-o" | "--output" => parsed.output = value()?,
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option `{}`", arg))
+            }
+            _ if font.is_none() => font = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    parsed.font = font.ok_or("missing font file")?;
+    if parsed.chars.is_empty() {
+        parsed.chars.push('A');
+    }
+    Ok(Some(parsed))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number `{}`", value))
+}
+
+/// Parses a character or a codepoint like `U+0041` or `0x41`.
+fn parse_char(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(c);
+    }
+    parse_codepoint(value)
+}
+
+fn parse_codepoint(value: &str) -> Result<char, String> {
+    let value = value.trim();
+    let codepoint = match value
+        .strip_prefix("U+")
+        .or_else(|| value.strip_prefix("0x"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    };
+    codepoint
+        .and_then(char::from_u32)
+        .ok_or_else(|| format!("invalid codepoint `{}`", value))
+}
+
+fn parse_codepoints(value: &str) -> Result<Vec<char>, String> {
+    let (from, to) = value
+        .split_once('-')
+        .ok_or_else(|| format!("invalid codepoint range `{}`", value))?;
+    let (from, to) = (parse_codepoint(from)?, parse_codepoint(to)?);
+    if from > to {
+        return Err(format!("reversed codepoint range `{}`", value));
+    }
+    Ok((from..=to).collect())
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let data = fs::read(&args.font)
+        .map_err(|err| format!("can't read {}: {}", args.font, err))?;
+    let font = Font::from_collection_vec(data, args.face)
+        .map_err(|err| format!("can't load {}: {}", args.font, err))?;
+    let settings = AtlasSettings {
+        field_type: args.field_type,
+        scale: Scale::uniform(args.size),
+        range: args.range,
+        offset: Offset::uniform(args.padding),
+        page_width: args.atlas_size.0,
+        page_height: args.atlas_size.1,
+        y_axis: args.y_axis,
+        ..Default::default()
+    };
+
+    if let [c] = args.chars[..] {
+        let outline = font
+            .try_glyph(c)
+            .and_then(|glyph| glyph.build(settings.scale, settings.offset))
+            .map_err(|err| err.to_string())?;
+        // Same config as the atlas glyphs.
+        let config = settings.generator_config().y_axis(args.y_axis);
+        // The default format is `u8`.
+        let bitmap = outline.generate(&config).into_u8().unwrap();
+        save_image(&bitmap, &format!("{}.png", args.output))?;
+
+        let mut origin = outline.origin();
+        if args.y_axis == YAxis::Up {
+            origin.y = bitmap.height as f32 - origin.y;
+        }
+        let range = args.range.to_pixels(outline.pixels_per_em());
+        let json = format!(
+            "{{\"unicode\":{},\"type\":\"{}\",\"distanceRange\":{},\
+            \"distanceRangeMiddle\":{},\"width\":{},\"height\":{},\
+            \"originX\":{},\"originY\":{},\"advance\":{},\"yOrigin\":\"{}\"}}",
+            c as u32,
+            mode_name(args.field_type),
+            range.width(),
+            range.middle(),
+            bitmap.width,
+            bitmap.height,
+            origin.x,
+            origin.y,
+            outline.h_metrics().advance_width,
+            match args.y_axis {
+                YAxis::Down => "top",
+                YAxis::Up => "bottom",
+            }
+        );
+        return write_file(&format!("{}.json", args.output), json);
+    }
+
+    let atlas = Atlas::generate(&font, args.chars.iter().copied(), settings)
+        .map_err(|err| err.to_string())?;
+    for (i, page) in atlas.pages.iter().enumerate() {
+        save_image(page, &format!("{}_{}.png", args.output, i))?;
+    }
+    write_file(&format!("{}.json", args.output), atlas.to_json(&font))
+}
+
+fn save_image(bitmap: &Bitmap, path: &str) -> Result<(), String> {
+    bitmap
+        .save_png(path)
+        .map_err(|err| format!("can't write {}: {}", path, err))
+}
+
+fn write_file(path: &str, contents: String) -> Result<(), String> {
+    fs::write(path, contents)
+        .map_err(|err| format!("can't write {}: {}", path, err))
+}
+
+fn mode_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Sdf => "sdf",
+        FieldType::PseudoSdf => "psdf",
+        FieldType::Msdf => "msdf",
+        FieldType::Mtsdf => "mtsdf",
+    }
+}
+
+#[test]
+fn parse_args_test() {
+    let parse = |args: &str| parse_args(args.split(' ').map(String::from));
+
+    let args = parse("font.ttf -c B --codepoints 0x20-0x22 -m sdf --y-up")
+        .unwrap()
+        .unwrap();
+    assert_eq!(args.font, "font.ttf");
+    assert_eq!(args.chars, ['B', ' ', '!', '"']);
+    assert_eq!(args.field_type, FieldType::Sdf);
+    assert_eq!(args.y_axis, YAxis::Up);
+
+    let args = parse("font.ttf -r 0.125em --atlas-size 256x128 -f 2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(args.chars, ['A']);
+    assert_eq!(args.range, Range::ems(0.125));
+    assert_eq!(args.atlas_size, (256, 128));
+    assert_eq!(args.face, 2);
+
+    assert!(parse("font.ttf --help").unwrap().is_none());
+    assert!(parse("-c A").is_err());
+    assert!(parse("font.ttf -m bad").is_err());
+    assert!(parse("font.ttf --size").is_err());
+    assert!(parse("font.ttf other.ttf").is_err());
+}
+
+#[test]
+fn parse_codepoints_test() {
+    assert_eq!(parse_char("@"), Ok('@'));
+    assert_eq!(parse_char("U+0041"), Ok('A'));
+    assert_eq!(parse_codepoint("0x7e"), Ok('~'));
+    assert_eq!(parse_codepoint("65"), Ok('A'));
+    assert!(parse_codepoint("U+D800").is_err());
+    assert!(parse_codepoint("abc").is_err());
+
+    assert_eq!(parse_codepoints("U+0041-U+0043"), Ok(vec!['A', 'B', 'C']));
+    assert_eq!(parse_codepoints("0x41-0x41"), Ok(vec!['A']));
+    assert!(parse_codepoints("0x7e-0x20").is_err());
+    assert!(parse_codepoints("0x20").is_err());
+}
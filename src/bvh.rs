@@ -0,0 +1,420 @@
+use crate::{
+    math::Distance,
+    shape::{Segment, Shape},
+    vector::Vector2,
+};
+
+/// Maximum number of segments in a leaf node.
+const LEAF_SIZE: usize = 4;
+
+/// Segments whose bounding box is further than the closest found distance
+/// plus this margin are skipped.
+const MARGIN: f32 = 0.1;
+
+/// Distances closer than `0.01` are compared by orthogonality (see
+/// `Distance::partial_cmp`), so the result depends on the order of
+/// comparisons. Segments within this range of each other are always
+/// compared exactly. Includes some slack for float errors.
+const TIE_RANGE: f32 = 0.011;
+
+/// Channel bits used for selecting segments. Red, green and blue match
+/// the [`EdgeColor`](crate::EdgeColor) bits.
+const RED: u8 = 0b0001;
+const GREEN: u8 = 0b0010;
+const BLUE: u8 = 0b0100;
+/// Channel which contains all segments regardless of their color.
+const ALL: u8 = 0b1000;
+
+/// Bounding volume hierarchy over segment bounding boxes of a [`Shape`].
+///
+/// Used for skipping segments which are too far from the pixel to be the
+/// closest one. Results are identical to comparing the distance of every
+/// segment in order.
+pub(crate) struct Bvh<'a> {
+    shape: &'a Shape,
+    /// Segments in the same order as in the shape.
+    entries: Vec<Entry<'a>>,
+    /// Entry indices ordered so that each leaf covers a continuous range.
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+struct Entry<'a> {
+    segment: &'a Segment,
+    contour: usize,
+    /// Channel bits of the segment, including [`ALL`].
+    channels: u8,
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+}
+
+struct Node {
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+    /// Channel bits of all segments in the node.
+    channels: u8,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Leaf { start: usize, end: usize },
+    Inner { left: usize, right: usize },
+}
+
+/// Segment distances evaluated during one query.
+struct Candidates {
+    /// Entry index and distance.
+    distances: Vec<(usize, Distance)>,
+    /// Closest real distance of each channel, indexed by the channel bit
+    /// position.
+    closest: [f32; 4],
+}
+
+impl<'a> Bvh<'a> {
+    pub fn new(shape: &'a Shape) -> Self {
+        let mut entries = Vec::new();
+        for (contour_index, contour) in shape.contours.iter().enumerate() {
+            for (segment, color) in contour.iter_colored() {
+                let (min, max) = segment.bounds();
+                entries.push(Entry {
+                    segment,
+                    contour: contour_index,
+                    channels: color.bits() | ALL,
+                    min,
+                    max,
+                });
+            }
+        }
+
+        let mut bvh = Self {
+            shape,
+            order: (0..entries.len()).collect(),
+            entries,
+            nodes: Vec::new(),
+        };
+        if !bvh.entries.is_empty() {
+            bvh.build_node(0, bvh.entries.len());
+        }
+        bvh
+    }
+
+    /// Builds a node over `order[start..end]` and returns its index.
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let mut min = Vector2::new(f32::MAX, f32::MAX);
+        let mut max = Vector2::new(f32::MIN, f32::MIN);
+        let mut channels = 0;
+        for &i in &self.order[start..end] {
+            let entry = &self.entries[i];
+            min = Vector2::new(min.x.min(entry.min.x), min.y.min(entry.min.y));
+            max = Vector2::new(max.x.max(entry.max.x), max.y.max(entry.max.y));
+            channels |= entry.channels;
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            channels,
+            kind: NodeKind::Leaf { start, end },
+        });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        // Split at the median of segment centers along the longer axis.
+        let entries = &self.entries;
+        let center = |i: usize| (entries[i].min + entries[i].max) * 0.5;
+        if max.x - min.x > max.y - min.y {
+            self.order[start..end]
+                .sort_by(|&a, &b| center(a).x.total_cmp(&center(b).x));
+        } else {
+            self.order[start..end]
+                .sort_by(|&a, &b| center(a).y.total_cmp(&center(b).y));
+        }
+        let mid = (start + end) / 2;
+        let left = self.build_node(start, mid);
+        let right = self.build_node(mid, end);
+        self.nodes[index].kind = NodeKind::Inner { left, right };
+
+        index
+    }
+
+    /// Returns the closest [`Distance`] in the same way as comparing
+    /// [`Contour::distance`](crate::shape::Contour::distance) of each
+    /// contour.
+    pub fn shortest_distance(&self, point: Vector2<f32>) -> Distance {
+        let candidates = self.candidates(point, ALL);
+        let near = match self.near(&candidates, ALL) {
+            Some(near) => near,
+            None => {
+                return self
+                    .shape
+                    .contours
+                    .iter()
+                    .map(|contour| contour.distance(point))
+                    .min_by(|a, b| a.partial_cmp(b).unwrap())
+                    .expect("Error: There are no distances??")
+            }
+        };
+
+        // Segments are closest within their contour first, then contours
+        // are compared with each other.
+        let mut contour_distances = Vec::new();
+        let mut current: Option<(usize, Distance)> = None;
+        for (i, distance) in near {
+            let contour = self.entries[i].contour;
+            current = match current {
+                Some((c, accum)) if c == contour => {
+                    Some((c, if accum < distance { accum } else { distance }))
+                }
+                Some((_, accum)) => {
+                    contour_distances.push(accum);
+                    Some((contour, distance))
+                }
+                None => Some((contour, distance)),
+            };
+        }
+        contour_distances.extend(current.map(|(_, distance)| distance));
+
+        contour_distances
+            .into_iter()
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .expect("Error: There are no distances??")
+    }
+
+    /// Returns the closest [`Distance`] of segments containing the red,
+    /// green and blue color and of all segments, in that order.
+    ///
+    /// Channels without any segments hold [`Distance::MAX`].
+    pub fn shortest_multi_distance(
+        &self,
+        point: Vector2<f32>,
+    ) -> [Distance; 4] {
+        let candidates = self.candidates(point, RED | GREEN | BLUE | ALL);
+
+        [RED, GREEN, BLUE, ALL].map(|channel| {
+            let mut shortest = Distance::MAX;
+            match self.near(&candidates, channel) {
+                Some(near) => {
+                    for (_, distance) in near {
+                        if distance < shortest {
+                            shortest = distance;
+                        }
+                    }
+                }
+                None => {
+                    for entry in &self.entries {
+                        if entry.channels & channel == 0 {
+                            continue;
+                        }
+                        let distance = entry.segment.distance(point);
+                        if distance < shortest {
+                            shortest = distance;
+                        }
+                    }
+                }
+            }
+            shortest
+        })
+    }
+
+    /// Evaluates distances of all segments which could be the closest in
+    /// any of the requested channels.
+    fn candidates(&self, point: Vector2<f32>, channels: u8) -> Candidates {
+        let mut candidates = Candidates {
+            distances: Vec::new(),
+            closest: [f32::MAX; 4],
+        };
+        if self.nodes.is_empty() {
+            return candidates;
+        }
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let bound = box_distance(point, node.min, node.max);
+            if candidates.is_too_far(bound, node.channels & channels) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    for &i in &self.order[start..end] {
+                        let entry = &self.entries[i];
+                        let entry_channels = entry.channels & channels;
+                        let bound = box_distance(point, entry.min, entry.max);
+                        if candidates.is_too_far(bound, entry_channels) {
+                            continue;
+                        }
+                        let distance = entry.segment.distance(point);
+                        candidates.add(i, distance, entry_channels);
+                    }
+                }
+                NodeKind::Inner { left, right } => {
+                    // Visit the closer child first by pushing it last.
+                    let bound_of = |child: usize| {
+                        let child = &self.nodes[child];
+                        box_distance(point, child.min, child.max)
+                    };
+                    if bound_of(left) < bound_of(right) {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+
+        candidates
+    }
+
+    /// Returns candidates of the channel, in the shape order, which have to
+    /// be compared to get the same result as comparing all segments.
+    ///
+    /// Every other segment is further than all returned ones by more than
+    /// [`TIE_RANGE`] so it can never be chosen over them. Returns `None`
+    /// if that can't be guaranteed and all segments have to be compared.
+    fn near(
+        &self,
+        candidates: &Candidates,
+        channel: u8,
+    ) -> Option<Vec<(usize, Distance)>> {
+        let closest = candidates.closest[channel.trailing_zeros() as usize];
+        if closest == f32::MAX {
+            // There are no segments of this channel.
+            return Some(Vec::new());
+        }
+
+        let mut real_dists = candidates
+            .distances
+            .iter()
+            .filter(|(i, _)| self.entries[*i].channels & channel != 0)
+            .map(|(_, distance)| distance.real_dist)
+            .collect::<Vec<_>>();
+        real_dists.sort_by(f32::total_cmp);
+
+        // Extend the limit while other candidates are within the tie range.
+        let mut limit = closest;
+        for real_dist in real_dists {
+            if real_dist <= limit {
+                continue;
+            }
+            if real_dist - limit > TIE_RANGE {
+                break;
+            }
+            limit = real_dist;
+        }
+        // Skipped segments are only guaranteed to be beyond this distance.
+        if limit + TIE_RANGE >= closest + MARGIN {
+            return None;
+        }
+
+        let mut near = candidates
+            .distances
+            .iter()
+            .filter(|(i, distance)| {
+                self.entries[*i].channels & channel != 0
+                    && distance.real_dist <= limit
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        near.sort_by_key(|(i, _)| *i);
+        Some(near)
+    }
+}
+
+impl Candidates {
+    /// Checks if segments at least `bound` away can't be the closest in
+    /// any of the provided channels.
+    fn is_too_far(&self, bound: f32, channels: u8) -> bool {
+        (0..4).all(|bit| {
+            channels & (1 << bit) == 0 || bound > self.closest[bit] + MARGIN
+        })
+    }
+
+    fn add(&mut self, index: usize, distance: Distance, channels: u8) {
+        for bit in 0..4 {
+            if channels & (1 << bit) != 0 {
+                let closest = &mut self.closest[bit];
+                *closest = closest.min(distance.real_dist);
+            }
+        }
+        self.distances.push((index, distance));
+    }
+}
+
+/// Distance from the point to the closest point of the box, zero if the
+/// point is inside.
+#[inline]
+fn box_distance(
+    point: Vector2<f32>,
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+) -> f32 {
+    let dx = (min.x - point.x).max(point.x - max.x).max(0.0);
+    let dy = (min.y - point.y).max(point.y - max.y).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[test]
+fn bvh_test() {
+    use crate::{
+        coloring::{ColoringStrategy, DEFAULT_ANGLE_THRESHOLD},
+        font::{Font, Offset},
+    };
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    for c in ['@', 'g', 'W'] {
+        let mut outline = font
+            .glyph(c)
+            .build(Scale::uniform(40.0), Offset::uniform(3.0))
+            .unwrap();
+        outline.color_edges(ColoringStrategy::InkTrap, DEFAULT_ANGLE_THRESHOLD);
+        let shape = &outline.shape;
+        let bvh = Bvh::new(shape);
+        let same = |a: Distance, b: Distance| {
+            a.real_dist == b.real_dist
+                && a.extended_dist == b.extended_dist
+                && a.sign.to_bits() == b.sign.to_bits()
+        };
+
+        for y in 0..outline.height() + 6 {
+            for x in 0..outline.width() + 6 {
+                let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+                // Compare against every segment in order.
+                let expected = shape
+                    .contours
+                    .iter()
+                    .map(|contour| contour.distance(pixel))
+                    .min_by(|a, b| a.partial_cmp(b).unwrap())
+                    .unwrap();
+                let distance = bvh.shortest_distance(pixel);
+                assert!(same(distance, expected));
+
+                let mut expected = [Distance::MAX; 4];
+                for contour in &shape.contours {
+                    for (segment, color) in contour.iter_colored() {
+                        let distance = segment.distance(pixel);
+                        for (i, channel) in
+                            [RED, GREEN, BLUE, ALL].into_iter().enumerate()
+                        {
+                            if (color.bits() | ALL) & channel != 0
+                                && distance < expected[i]
+                            {
+                                expected[i] = distance;
+                            }
+                        }
+                    }
+                }
+                let distances = bvh.shortest_multi_distance(pixel);
+                for (a, b) in distances.into_iter().zip(expected) {
+                    assert!(same(a, b));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,523 @@
+use std::fmt;
+
+use crate::shape::{Contour, Segment, Shape};
+use crate::vector::Vector2;
+
+/// Default angle (in radians) under which a joint of two segments
+/// is considered to be a corner.
+pub const DEFAULT_ANGLE_THRESHOLD: f32 = 3.0;
+
+/// Color of a segment (edge) which determines in which channels of
+/// the multi-channel distance field the segment takes part.
+///
+/// Colors are represented as bit flags where red is `0b001`,
+/// green is `0b010` and blue is `0b100`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum EdgeColor {
+    Black = 0b000,
+    Red = 0b001,
+    Green = 0b010,
+    Yellow = 0b011,
+    Blue = 0b100,
+    Magenta = 0b101,
+    Cyan = 0b110,
+    White = 0b111,
+}
+
+impl EdgeColor {
+    #[inline]
+    pub fn from_bits(bits: u8) -> Self {
+        match bits & 0b111 {
+            0b000 => EdgeColor::Black,
+            0b001 => EdgeColor::Red,
+            0b010 => EdgeColor::Green,
+            0b011 => EdgeColor::Yellow,
+            0b100 => EdgeColor::Blue,
+            0b101 => EdgeColor::Magenta,
+            0b110 => EdgeColor::Cyan,
+            _ => EdgeColor::White,
+        }
+    }
+
+    #[inline]
+    pub fn bits(self) -> u8 {
+        self as u8
+    }
+
+    #[inline]
+    pub fn has_red(self) -> bool {
+        self.bits() & EdgeColor::Red.bits() != 0
+    }
+
+    #[inline]
+    pub fn has_green(self) -> bool {
+        self.bits() & EdgeColor::Green.bits() != 0
+    }
+
+    #[inline]
+    pub fn has_blue(self) -> bool {
+        self.bits() & EdgeColor::Blue.bits() != 0
+    }
+}
+
+impl fmt::Display for EdgeColor {
+    /// Writes the color as a single letter which is handy for
+    /// inspecting colorings of a whole contour.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            EdgeColor::Black => 'k',
+            EdgeColor::Red => 'r',
+            EdgeColor::Green => 'g',
+            EdgeColor::Yellow => 'y',
+            EdgeColor::Blue => 'b',
+            EdgeColor::Magenta => 'm',
+            EdgeColor::Cyan => 'c',
+            EdgeColor::White => 'w',
+        };
+        write!(f, "{}", letter)
+    }
+}
+
+/// Strategy used for assigning [`EdgeColor`]s to segments.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ColoringStrategy {
+    /// Switches the color at each corner. Fast and good enough for
+    /// most glyphs.
+    #[default]
+    Simple,
+    /// Like [`ColoringStrategy::Simple`] but detects small segments
+    /// between two corners (*ink traps*) and colors them so that they
+    /// don't take a whole color for themselves.
+    InkTrap,
+    /// Chooses colors so that splines which are close to each other,
+    /// even if they belong to different contours, get different colors.
+    /// Slowest, but produces the least artifacts.
+    Distance,
+}
+
+/// Assigns edge colors to all contours of the shape using the
+/// provided strategy.
+///
+/// Joints of two segments are considered as corners if the angle
+/// between their directions is bigger than `angle_threshold` (radians).
+pub fn color_edges(
+    shape: &mut Shape,
+    strategy: ColoringStrategy,
+    angle_threshold: f32,
+) {
+    match strategy {
+        ColoringStrategy::Simple => color_simple(shape, angle_threshold),
+        ColoringStrategy::InkTrap => color_ink_trap(shape, angle_threshold),
+        ColoringStrategy::Distance => color_by_distance(shape, angle_threshold),
+    }
+}
+
+/// Assigns edge colors to all contours of the shape so that segments
+/// which meet at a corner never share more than one channel.
+///
+/// Contours without corners are colored white, contours with a single
+/// corner (*teardrops*) are split into three differently colored parts
+/// and all other contours switch color at each corner.
+pub fn color_simple(shape: &mut Shape, angle_threshold: f32) {
+    let cross_threshold = angle_threshold.sin();
+    let mut seed = 0;
+
+    for contour in shape.contours.iter_mut() {
+        let corners = find_corners(contour, cross_threshold);
+
+        match corners.len() {
+            0 => contour.colors.fill(EdgeColor::White),
+            1 => color_teardrop(contour, corners[0], &mut seed),
+            _ => color_corners(contour, &corners, &mut seed),
+        }
+    }
+}
+
+/// Same as [`color_simple`] except that for contours with more than three
+/// corners, corners which are followed by a spline shorter than both of
+/// its neighbors (*minor* corners) don't switch to a completely new color.
+/// Instead, the short spline gets a color which shares one channel with
+/// each of its neighbors.
+pub fn color_ink_trap(shape: &mut Shape, angle_threshold: f32) {
+    let cross_threshold = angle_threshold.sin();
+    let mut seed = 0;
+
+    for contour in shape.contours.iter_mut() {
+        let corners = find_corners(contour, cross_threshold);
+
+        match corners.len() {
+            0 => contour.colors.fill(EdgeColor::White),
+            1 => color_teardrop(contour, corners[0], &mut seed),
+            _ => color_ink_trap_corners(contour, &corners, &mut seed),
+        }
+    }
+}
+
+/// Colors contours with at least two corners so that splines (segments
+/// between two corners) close to each other don't share the same color.
+///
+/// Colors are picked one spline at a time. Each spline must differ from
+/// its neighbors in the contour and from the rest, the color which is
+/// the furthest from other splines of the same color is chosen.
+pub fn color_by_distance(shape: &mut Shape, angle_threshold: f32) {
+    const COLORS: [EdgeColor; 3] =
+        [EdgeColor::Cyan, EdgeColor::Magenta, EdgeColor::Yellow];
+
+    let cross_threshold = angle_threshold.sin();
+    let mut seed = 0;
+    // Sampled points and colors of already colored splines.
+    let mut colored: Vec<(Vec<Vector2<f32>>, EdgeColor)> = Vec::new();
+
+    for contour in shape.contours.iter_mut() {
+        let corners = find_corners(contour, cross_threshold);
+
+        match corners.len() {
+            0 => contour.colors.fill(EdgeColor::White),
+            1 => color_teardrop(contour, corners[0], &mut seed),
+            _ => {
+                let count = contour.segments.len();
+                let first_spline = colored.len();
+                for (i, &corner) in corners.iter().enumerate() {
+                    let end = corners.get(i + 1).copied().unwrap_or(corners[0]);
+                    let len = (end + count - corner - 1) % count + 1;
+                    let indices = (0..len).map(|j| (corner + j) % count);
+
+                    let samples = indices
+                        .clone()
+                        .flat_map(|index| {
+                            sample_points(&contour.segments[index])
+                        })
+                        .collect::<Vec<_>>();
+
+                    let prev = (i > 0).then(|| colored[colored.len() - 1].1);
+                    let first = (i == corners.len() - 1)
+                        .then(|| colored[first_spline].1);
+                    let color = COLORS
+                        .into_iter()
+                        .filter(|&c| Some(c) != prev && Some(c) != first)
+                        .map(|c| {
+                            let dist = colored
+                                .iter()
+                                .filter(|(_, other)| *other == c)
+                                .map(|(other, _)| {
+                                    samples_distance(&samples, other)
+                                })
+                                .fold(f32::MAX, f32::min);
+                            (c, dist)
+                        })
+                        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                        .map(|(c, _)| c)
+                        .expect("At least one color is always allowed");
+
+                    for index in indices {
+                        contour.colors[index] = color;
+                    }
+                    colored.push((samples, color));
+                }
+            }
+        }
+    }
+}
+
+/// Returns indices of segments which start at a corner.
+fn find_corners(contour: &Contour, cross_threshold: f32) -> Vec<usize> {
+    let mut corners = Vec::new();
+    let mut prev_dir = match contour.segments.last() {
+        Some(last) => last.direction(1.0),
+        None => return corners,
+    };
+
+    for (i, segment) in contour.iter().enumerate() {
+        if is_corner(prev_dir, segment.direction(0.0), cross_threshold) {
+            corners.push(i);
+        }
+        prev_dir = segment.direction(1.0);
+    }
+
+    corners
+}
+
+/// Two directions form a corner if they are opposite or if the
+/// angle between them is bigger than the threshold.
+fn is_corner(a: Vector2<f32>, b: Vector2<f32>, cross_threshold: f32) -> bool {
+    if a.is_zero() || b.is_zero() {
+        return false;
+    }
+    let a = a.normalize();
+    let b = b.normalize();
+
+    a.dot(b) <= 0.0 || a.cross(b).abs() > cross_threshold
+}
+
+/// Switches to a next color which differs from the `banned` one.
+fn switch_color(color: &mut EdgeColor, seed: &mut u64, banned: EdgeColor) {
+    let combined = color.bits() & banned.bits();
+
+    if combined == EdgeColor::Red.bits()
+        || combined == EdgeColor::Green.bits()
+        || combined == EdgeColor::Blue.bits()
+    {
+        *color = EdgeColor::from_bits(combined ^ EdgeColor::White.bits());
+    } else if *color == EdgeColor::Black || *color == EdgeColor::White {
+        const START: [EdgeColor; 3] =
+            [EdgeColor::Cyan, EdgeColor::Magenta, EdgeColor::Yellow];
+        *color = START[(*seed % 3) as usize];
+        *seed /= 3;
+    } else {
+        let shifted = color.bits() << (1 + (*seed & 1));
+        *color = EdgeColor::from_bits(shifted | shifted >> 3);
+        *seed >>= 1;
+    }
+}
+
+fn color_corners(contour: &mut Contour, corners: &[usize], seed: &mut u64) {
+    let count = contour.segments.len();
+    let start = corners[0];
+    let mut spline = 0;
+
+    let mut color = EdgeColor::White;
+    switch_color(&mut color, seed, EdgeColor::Black);
+    let initial = color;
+
+    for i in 0..count {
+        let index = (start + i) % count;
+        if spline + 1 < corners.len() && corners[spline + 1] == index {
+            spline += 1;
+            // The last spline must also differ from the first one.
+            let banned = if spline == corners.len() - 1 {
+                initial
+            } else {
+                EdgeColor::Black
+            };
+            switch_color(&mut color, seed, banned);
+        }
+        contour.colors[index] = color;
+    }
+}
+
+fn color_ink_trap_corners(
+    contour: &mut Contour,
+    corners: &[usize],
+    seed: &mut u64,
+) {
+    let count = contour.segments.len();
+    let corner_count = corners.len();
+
+    // Estimated length of the spline preceding each corner.
+    let mut lengths = vec![0.0; corner_count];
+    for (i, &corner) in corners.iter().enumerate() {
+        let next = corners[(i + 1) % corner_count];
+        let len = (next + count - corner - 1) % count + 1;
+        lengths[(i + 1) % corner_count] = (0..len)
+            .map(|j| estimate_length(&contour.segments[(corner + j) % count]))
+            .sum();
+    }
+
+    let mut minor = vec![false; corner_count];
+    let mut major_count = corner_count;
+    if corner_count > 3 {
+        for i in 0..corner_count {
+            let next = lengths[(i + 1) % corner_count];
+            if lengths[i] > next && next < lengths[(i + 2) % corner_count] {
+                minor[i] = true;
+                major_count -= 1;
+            }
+        }
+    }
+
+    let mut corner_colors = vec![EdgeColor::Black; corner_count];
+    let mut color = EdgeColor::White;
+    let mut initial = EdgeColor::Black;
+    for i in (0..corner_count).filter(|&i| !minor[i]) {
+        major_count -= 1;
+        // The last major corner must also differ from the first one.
+        let banned = if major_count == 0 {
+            initial
+        } else {
+            EdgeColor::Black
+        };
+        switch_color(&mut color, seed, banned);
+        corner_colors[i] = color;
+        if initial == EdgeColor::Black {
+            initial = color;
+        }
+    }
+    for i in 0..corner_count {
+        if minor[i] {
+            let next = corner_colors[(i + 1) % corner_count];
+            corner_colors[i] = EdgeColor::from_bits(
+                (color.bits() & next.bits()) ^ EdgeColor::White.bits(),
+            );
+        } else {
+            color = corner_colors[i];
+        }
+    }
+
+    let start = corners[0];
+    let mut spline = 0;
+    let mut color = corner_colors[0];
+    for i in 0..count {
+        let index = (start + i) % count;
+        if spline + 1 < corner_count && corners[spline + 1] == index {
+            spline += 1;
+            color = corner_colors[spline];
+        }
+        contour.colors[index] = color;
+    }
+}
+
+/// Colors a contour with only one corner. Segments are split into three
+/// groups of colors where the middle one is white. If there are less than
+/// three segments, they are split in thirds first.
+fn color_teardrop(contour: &mut Contour, corner: usize, seed: &mut u64) {
+    let mut colors = [EdgeColor::White; 3];
+    switch_color(&mut colors[0], seed, EdgeColor::Black);
+    colors[2] = colors[0];
+    switch_color(&mut colors[2], seed, EdgeColor::Black);
+
+    let count = contour.segments.len();
+    if count >= 3 {
+        for i in 0..count {
+            let index = (corner + i) % count;
+            contour.colors[index] = colors[symmetrical_trichotomy(i, count)];
+        }
+        return;
+    }
+
+    // Start splitting from the corner so the parts are in order.
+    let mut segments = Vec::with_capacity(count * 3);
+    for i in 0..count {
+        let segment = contour.segments[(corner + i) % count];
+        segments.extend(segment.split_in_thirds());
+    }
+    let colors = if count == 2 {
+        vec![
+            colors[0], colors[0], colors[1], colors[1], colors[2], colors[2],
+        ]
+    } else {
+        colors.to_vec()
+    };
+
+    contour.segments = segments;
+    contour.colors = colors;
+}
+
+/// Estimates the length of the segment by summing up distances
+/// between a few points on the segment.
+fn estimate_length(segment: &Segment) -> f32 {
+    let points = sample_points(segment);
+    points
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).magnitude())
+        .sum()
+}
+
+/// Returns evenly (by the parameter `t`) distributed points on the segment.
+fn sample_points(segment: &Segment) -> Vec<Vector2<f32>> {
+    const PRECISION: usize = 4;
+    (0..=PRECISION)
+        .map(|i| segment.point(i as f32 / PRECISION as f32))
+        .collect()
+}
+
+/// Returns the smallest distance between any two points of both sets.
+fn samples_distance(a: &[Vector2<f32>], b: &[Vector2<f32>]) -> f32 {
+    a.iter()
+        .flat_map(|p| b.iter().map(move |q| (*q - *p).magnitude()))
+        .fold(f32::MAX, f32::min)
+}
+
+/// Maps `position` in range `[0, n - 1]` to `0`, `1` or `2`
+/// symmetrically around the middle.
+fn symmetrical_trichotomy(position: usize, n: usize) -> usize {
+    (3.0 + 2.875 * position as f32 / (n as f32 - 1.0) - 1.4375 + 0.5) as usize
+        - 2
+}
+
+#[test]
+fn color_simple_test() {
+    use crate::shape::{Line, Quad, Segment, Winding};
+
+    let square = Contour::new(
+        vec![
+            Segment::Line(Line::new(
+                Vector2::new(0.0, 0.0),
+                Vector2::new(0.0, 1.0),
+            )),
+            Segment::Line(Line::new(
+                Vector2::new(0.0, 1.0),
+                Vector2::new(1.0, 1.0),
+            )),
+            Segment::Line(Line::new(
+                Vector2::new(1.0, 1.0),
+                Vector2::new(1.0, 0.0),
+            )),
+            Segment::Line(Line::new(
+                Vector2::new(1.0, 0.0),
+                Vector2::new(0.0, 0.0),
+            )),
+        ],
+        Winding(true),
+    );
+    let teardrop = Contour::new(
+        vec![Segment::Quad(Quad::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(5.0, 10.0),
+            Vector2::new(0.0, 0.0),
+        ))],
+        Winding(true),
+    );
+    let mut shape = Shape::new(vec![square, teardrop]);
+    color_simple(&mut shape, DEFAULT_ANGLE_THRESHOLD);
+
+    // Segments meeting at a corner share at most one channel.
+    let colors = shape.contours[0].colors();
+    for i in 0..colors.len() {
+        let shared = colors[i].bits() & colors[(i + 1) % colors.len()].bits();
+        assert!(shared.count_ones() <= 1);
+    }
+
+    // Single segment teardrop is split in thirds.
+    assert_eq!(shape.contours[1].segments.len(), 3);
+    assert_eq!(shape.contours[1].colors()[1], EdgeColor::White);
+}
+
+#[test]
+fn coloring_strategies_test() {
+    use crate::shape::{Line, Segment, Winding};
+
+    // Square with a small notch (ink trap) at the bottom.
+    let points = [
+        (0.0, 0.0),
+        (4.5, 0.0),
+        (5.0, 0.5),
+        (5.5, 0.0),
+        (10.0, 0.0),
+        (10.0, 10.0),
+        (0.0, 10.0),
+    ];
+    for strategy in [
+        ColoringStrategy::Simple,
+        ColoringStrategy::InkTrap,
+        ColoringStrategy::Distance,
+    ] {
+        let segments = (0..points.len())
+            .map(|i| {
+                let from = Vector2::from(points[i]);
+                let to = Vector2::from(points[(i + 1) % points.len()]);
+                Segment::Line(Line::new(from, to))
+            })
+            .collect();
+        let mut shape = Shape::new(vec![Contour::new(segments, Winding(true))]);
+        color_edges(&mut shape, strategy, DEFAULT_ANGLE_THRESHOLD);
+
+        // Each joint is a corner so neighbors must differ.
+        let colors = shape.contours[0].colors();
+        for i in 0..colors.len() {
+            let next = colors[(i + 1) % colors.len()];
+            assert_ne!(colors[i], next, "{:?}", strategy);
+            assert!((colors[i].bits() & next.bits()).count_ones() <= 1);
+        }
+    }
+}
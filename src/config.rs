@@ -0,0 +1,295 @@
+use crate::{
+    correction::{self, ErrorCorrection},
+    font::GlyphOutline,
+    gen::{self, Bitmap, FieldType, FloatBitmap},
+    range::Range,
+    shape::FillRule,
+};
+
+/// Direction in which the rows of generated bitmaps go.
+///
+/// Shapes are always built with `y` increasing downwards. `Down` fits
+/// APIs with the texture origin in the top left corner, like Vulkan,
+/// DirectX and most image formats, while `Up` fits OpenGL where the first
+/// row of a texture is at the bottom.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YAxis {
+    /// The first row is the top of the glyph.
+    #[default]
+    Down,
+    /// The first row is the bottom of the glyph.
+    Up,
+}
+
+/// Texel type of generated bitmaps.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputFormat {
+    /// 8-bit normalized values.
+    #[default]
+    U8,
+    /// 16-bit normalized values.
+    U16,
+    /// Raw signed distances in pixels. The range is not applied.
+    F32,
+}
+
+/// Bitmap generated by [`GlyphOutline::generate`] in the configured
+/// [`OutputFormat`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputBitmap {
+    U8(Bitmap<u8>),
+    U16(Bitmap<u16>),
+    F32(FloatBitmap),
+}
+
+impl OutputBitmap {
+    pub fn width(&self) -> u32 {
+        match self {
+            OutputBitmap::U8(bitmap) => bitmap.width,
+            OutputBitmap::U16(bitmap) => bitmap.width,
+            OutputBitmap::F32(bitmap) => bitmap.width,
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            OutputBitmap::U8(bitmap) => bitmap.height,
+            OutputBitmap::U16(bitmap) => bitmap.height,
+            OutputBitmap::F32(bitmap) => bitmap.height,
+        }
+    }
+
+    /// Returns the number of interleaved channels per pixel.
+    pub fn channels(&self) -> u32 {
+        match self {
+            OutputBitmap::U8(bitmap) => bitmap.channels,
+            OutputBitmap::U16(bitmap) => bitmap.channels,
+            OutputBitmap::F32(bitmap) => bitmap.channels,
+        }
+    }
+
+    /// Returns the bitmap if it has `u8` texels.
+    pub fn into_u8(self) -> Option<Bitmap<u8>> {
+        match self {
+            OutputBitmap::U8(bitmap) => Some(bitmap),
+            _ => None,
+        }
+    }
+
+    /// Returns the bitmap if it has `u16` texels.
+    pub fn into_u16(self) -> Option<Bitmap<u16>> {
+        match self {
+            OutputBitmap::U16(bitmap) => Some(bitmap),
+            _ => None,
+        }
+    }
+
+    /// Returns the bitmap if it has `f32` texels.
+    pub fn into_f32(self) -> Option<FloatBitmap> {
+        match self {
+            OutputBitmap::F32(bitmap) => Some(bitmap),
+            _ => None,
+        }
+    }
+}
+
+/// All settings of the distance field generation, used by
+/// [`GlyphOutline::generate`].
+///
+/// Fields can be set directly or with the builder methods:
+///
+/// ```
+/// use msdfont::{FieldType, FillRule, GeneratorConfig, Range};
+///
+/// let config = GeneratorConfig::new()
+///     .field_type(FieldType::Mtsdf)
+///     .range(Range::ems(0.125))
+///     .fill_rule(FillRule::EvenOdd)
+///     .sign_correction(true);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneratorConfig {
+    /// Type of the generated distance field.
+    pub field_type: FieldType,
+    /// Range of distances mapped to the output values.
+    pub range: Range,
+    /// Rule which decides what is inside of the shape, used by the overlap
+    /// and sign correction.
+    pub fill_rule: FillRule,
+    /// Fixes the sign of pixels by the scanline fill of the shape, see
+    /// [`GlyphOutline::correct_sign`].
+    pub sign_correction: bool,
+    /// Removes artifacts of multi-channel distance fields, see
+    /// [`GlyphOutline::correct_errors`].
+    pub error_correction: Option<ErrorCorrection>,
+    /// Direction in which the bitmap rows go.
+    pub y_axis: YAxis,
+    /// Texel type of the generated bitmap.
+    pub format: OutputFormat,
+    /// Merges overlapping contours before the generation so that the
+    /// distance field matches the fill rule.
+    pub overlap_correction: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            field_type: FieldType::default(),
+            range: Range::pixels(4.0),
+            fill_rule: FillRule::default(),
+            sign_correction: false,
+            error_correction: Some(ErrorCorrection::default()),
+            y_axis: YAxis::default(),
+            format: OutputFormat::default(),
+            overlap_correction: true,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    /// Creates the default config, same as [`GeneratorConfig::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field_type(mut self, field_type: FieldType) -> Self {
+        self.field_type = field_type;
+        self
+    }
+
+    pub fn range(mut self, range: impl Into<Range>) -> Self {
+        self.range = range.into();
+        self
+    }
+
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    pub fn sign_correction(mut self, sign_correction: bool) -> Self {
+        self.sign_correction = sign_correction;
+        self
+    }
+
+    pub fn error_correction(
+        mut self,
+        error_correction: Option<ErrorCorrection>,
+    ) -> Self {
+        self.error_correction = error_correction;
+        self
+    }
+
+    pub fn y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn overlap_correction(mut self, overlap_correction: bool) -> Self {
+        self.overlap_correction = overlap_correction;
+        self
+    }
+}
+
+/// Generates the distance field with all passes enabled in the config.
+///
+/// The outline has to have the shape matching the overlap correction and
+/// the fill rule of the config.
+pub fn generate(
+    outline: &GlyphOutline,
+    config: &GeneratorConfig,
+) -> OutputBitmap {
+    let mut bitmap = gen::gen_float(outline, config.field_type);
+    if config.sign_correction {
+        gen::correct_sign_float(outline, &mut bitmap, config.fill_rule);
+    }
+    if let Some(error_correction) = config.error_correction {
+        correction::correct_errors_float(
+            outline,
+            &mut bitmap,
+            error_correction,
+        );
+    }
+    if config.y_axis == YAxis::Up {
+        bitmap.flip_y();
+    }
+
+    let range = outline.pixel_range(config.range);
+    match config.format {
+        OutputFormat::U8 => OutputBitmap::U8(bitmap.to_u8(range)),
+        OutputFormat::U16 => OutputBitmap::U16(bitmap.to_u16(range)),
+        OutputFormat::F32 => OutputBitmap::F32(bitmap),
+    }
+}
+
+#[test]
+fn generator_config_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('@')
+        .build(Scale::uniform(32.0), Offset::uniform(2.0))
+        .unwrap();
+
+    let plain = GeneratorConfig::new().error_correction(None);
+    let msdf = outline.generate(&plain).into_u8().unwrap();
+    assert_eq!(msdf, outline.generate_msdf(4));
+
+    let float = plain.format(OutputFormat::F32);
+    let float = outline.generate(&float).into_f32().unwrap();
+    assert_eq!(float, outline.generate_float(FieldType::Msdf));
+
+    let mut flipped = outline
+        .generate(&plain.y_axis(YAxis::Up))
+        .into_u8()
+        .unwrap();
+    flipped.flip_y();
+    assert_eq!(flipped, msdf);
+
+    let wide = outline.generate(&plain.format(OutputFormat::U16));
+    assert_eq!(wide.into_u16(), Some(outline.generate_msdf_u16(4)));
+}
+
+#[test]
+fn overlap_config_test() {
+    use crate::{font::Offset, path::ShapeBuilder};
+
+    // Two overlapping squares with the same winding.
+    let mut builder = ShapeBuilder::new(30, 30, None, Offset::uniform(0.0));
+    for (x, y) in [(4.0, 4.0), (12.0, 12.0)] {
+        builder.open_at(x, y);
+        builder.line_to(x + 14.0, y);
+        builder.line_to(x + 14.0, y + 14.0);
+        builder.line_to(x, y + 14.0);
+        builder.line_to(x, y);
+        builder.close().unwrap();
+    }
+    let (shape, bbox) = builder.build().unwrap();
+    let outline = GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
+    let config = GeneratorConfig::new().field_type(FieldType::Sdf).range(16);
+    let overlap = |bitmap: OutputBitmap| {
+        let bitmap = bitmap.into_u8().unwrap();
+        bitmap.data[(15 * bitmap.width + 15) as usize]
+    };
+
+    // Overlapping area is inside of the merged shape.
+    let merged = overlap(outline.generate(&config));
+    assert!(merged > 127);
+    // Without the correction the edges of both squares remain close.
+    let kept = overlap(outline.generate(&config.overlap_correction(false)));
+    assert!(kept > 127 && kept < merged);
+    // The overlapping area is a hole by the even-odd rule.
+    let even_odd = config.fill_rule(FillRule::EvenOdd);
+    assert!(overlap(outline.generate(&even_odd)) < 128);
+}
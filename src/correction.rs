@@ -0,0 +1,262 @@
+use std::f32::consts::SQRT_2;
+
+use crate::{
+    bvh::Bvh,
+    font::GlyphOutline,
+    gen::{self, Bitmap, FloatBitmap},
+    math::median,
+    range::Range,
+    vector::Vector2,
+};
+
+/// Texels closer than this to the edge (in pixels) are considered to be
+/// at the edge.
+const EDGE_DISTANCE: f32 = 1.0;
+
+/// Decides which texels of a multi-channel distance field get corrected.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorCorrectionMode {
+    /// Corrects all detected artifacts.
+    Indiscriminate,
+    /// Corrects all detected artifacts except channel clashes at edges and
+    /// corners, which are needed for keeping them sharp.
+    #[default]
+    EdgePriority,
+    /// Only corrects texels at edges where the median has the wrong sign.
+    EdgeOnly,
+}
+
+/// Settings of the multi-channel distance field error correction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorCorrection {
+    pub mode: ErrorCorrectionMode,
+    /// Minimum difference between channels of neighboring texels (in
+    /// pixels) which is detected as a clash.
+    pub threshold: f32,
+}
+
+impl Default for ErrorCorrection {
+    fn default() -> Self {
+        Self {
+            mode: ErrorCorrectionMode::default(),
+            threshold: 1.001,
+        }
+    }
+}
+
+/// Removes artifacts of a multi-channel distance field (MSDF or MTSDF)
+/// generated from the outline with the provided range.
+///
+/// A texel is an artifact if its median has a different sign than the
+/// true signed distance, or if its channels clash with a neighboring texel
+/// so that interpolating between them would produce a false edge. Clashing
+/// texels get all channels set to their median while texels with the wrong
+/// sign get the true signed distance. Only the texel further from the edge
+/// of a clashing pair gets corrected.
+///
+/// For MTSDF the alpha channel is used as the true signed distance and
+/// stays unchanged. Single-channel bitmaps are left untouched.
+pub fn correct_errors(
+    outline: &GlyphOutline,
+    bitmap: &mut Bitmap,
+    range: Range,
+    correction: ErrorCorrection,
+) {
+    let range = outline.pixel_range(range);
+    let distances = FloatBitmap {
+        // Maps values back to signed distances in pixels, to the middle of
+        // the interval truncated to each value.
+        data: bitmap
+            .data
+            .iter()
+            .map(|&v| {
+                ((v as f32 + 0.5) / 255.0 - 0.5) * range.width()
+                    + range.middle()
+            })
+            .collect(),
+        width: bitmap.width,
+        height: bitmap.height,
+        channels: bitmap.channels,
+    };
+
+    for (i, distance) in find_errors(outline, &distances, correction) {
+        bitmap.data[i..i + 3].fill(gen::normalize(distance, &range));
+    }
+}
+
+/// Float variant of [`correct_errors`].
+pub fn correct_errors_float(
+    outline: &GlyphOutline,
+    bitmap: &mut FloatBitmap,
+    correction: ErrorCorrection,
+) {
+    for (i, distance) in find_errors(outline, bitmap, correction) {
+        bitmap.data[i..i + 3].fill(distance);
+    }
+}
+
+/// Returns the data index and the corrected distance of each artifact.
+fn find_errors(
+    outline: &GlyphOutline,
+    bitmap: &FloatBitmap,
+    correction: ErrorCorrection,
+) -> Vec<(usize, f32)> {
+    let channels = bitmap.channels as usize;
+    if channels < 3 || bitmap.data.is_empty() {
+        return Vec::new();
+    }
+    let (width, height) = (bitmap.width as usize, bitmap.height as usize);
+
+    let texel = |x: usize, y: usize| {
+        let i = (y * width + x) * channels;
+        [bitmap.data[i], bitmap.data[i + 1], bitmap.data[i + 2]]
+    };
+    let bvh = (channels == 3).then(|| Bvh::new(&outline.shape));
+    let true_distance = |x: usize, y: usize| match &bvh {
+        Some(bvh) => {
+            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            bvh.shortest_distance(pixel).real_signed()
+        }
+        None => bitmap.data[(y * width + x) * channels + 3],
+    };
+
+    let threshold = correction.threshold;
+    let neighbors = [
+        (-1, 0, threshold),
+        (1, 0, threshold),
+        (0, -1, threshold),
+        (0, 1, threshold),
+        (-1, -1, threshold * SQRT_2),
+        (1, -1, threshold * SQRT_2),
+        (-1, 1, threshold * SQRT_2),
+        (1, 1, threshold * SQRT_2),
+    ];
+
+    // Errors are collected first so corrections don't affect detection.
+    let mut errors = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let a = texel(x, y);
+            let clash = neighbors.iter().any(|&(dx, dy, threshold)| {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32
+                {
+                    return false;
+                }
+                detect_clash(a, texel(nx as usize, ny as usize), threshold)
+            });
+
+            let distance = true_distance(x, y);
+            let median = median(a[0], a[1], a[2]);
+            let wrong_sign =
+                distance != 0.0 && (median > 0.0) != (distance > 0.0);
+            let at_edge = distance.abs() < EDGE_DISTANCE;
+            // Clashes at edges keep edges and corners sharp.
+            let protected = at_edge && !wrong_sign;
+
+            let is_error = match correction.mode {
+                ErrorCorrectionMode::Indiscriminate => clash || wrong_sign,
+                ErrorCorrectionMode::EdgePriority => {
+                    (clash && !protected) || wrong_sign
+                }
+                ErrorCorrectionMode::EdgeOnly => at_edge && wrong_sign,
+            };
+            if is_error {
+                let value = if wrong_sign { distance } else { median };
+                errors.push(((y * width + x) * channels, value));
+            }
+        }
+    }
+    errors
+}
+
+/// Checks if interpolating between the two texels would produce a false
+/// edge because two channels change by more than the threshold (in
+/// pixels).
+///
+/// Ported from the legacy error correction of
+/// [msdfgen](https://github.com/Chlumsky/msdfgen).
+fn detect_clash(a: [f32; 3], b: [f32; 3], threshold: f32) -> bool {
+    // Sort channels so that pairs go from the biggest to the smallest
+    // absolute difference.
+    let mut pairs = [(a[0], b[0]), (a[1], b[1]), (a[2], b[2])];
+    pairs.sort_by(|p, q| (q.1 - q.0).abs().total_cmp(&(p.1 - p.0).abs()));
+    let [(_, b0), (a1, b1), (a2, b2)] = pairs;
+
+    (b1 - a1).abs() >= threshold
+        // Ignore if the other texel has already been equalized.
+        && !(b0 == b1 && b0 == b2)
+        // Only flag the texel which is further from the edge.
+        && a2.abs() >= b2.abs()
+}
+
+#[test]
+fn error_correction_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('@')
+        .build(Scale::uniform(32.0), Offset::uniform(3.0))
+        .unwrap();
+    let sdf = outline.generate_sdf(4);
+
+    // Corrected texels get the true distance quantized the same way as
+    // the generators do, or the median of the channels.
+    let median = |t: &[u8]| median(t[0] as f32, t[1] as f32, t[2] as f32);
+    let check_corrected = |before: &Bitmap, after: &Bitmap| {
+        let texels =
+            before.data.chunks_exact(3).zip(after.data.chunks_exact(3));
+        let mut changed = 0;
+        for ((before, after), &sdf) in texels.zip(&sdf.data) {
+            if before != after {
+                assert!(after.iter().all(|&v| v == after[0]));
+                assert!(after[0] == sdf || after[0] as f32 == median(before));
+                changed += 1;
+            }
+        }
+        changed
+    };
+    let wrong_signs = |msdf: &Bitmap| {
+        msdf.data
+            .chunks_exact(3)
+            .zip(&sdf.data)
+            .filter(|(texel, &sdf)| {
+                (median(texel) > 127.0) != (sdf > 127) && sdf.abs_diff(127) > 1
+            })
+            .count()
+    };
+
+    let mut changed = 0;
+    for mode in [
+        ErrorCorrectionMode::Indiscriminate,
+        ErrorCorrectionMode::EdgePriority,
+        ErrorCorrectionMode::EdgeOnly,
+    ] {
+        let mut msdf = outline.generate_msdf(4);
+        let correction = ErrorCorrection {
+            mode,
+            ..Default::default()
+        };
+        let uncorrected = msdf.clone();
+        correct_errors(&outline, &mut msdf, 4.into(), correction);
+        assert_eq!(wrong_signs(&msdf), 0);
+        changed += check_corrected(&uncorrected, &msdf);
+    }
+    assert!(changed > 0);
+
+    // Nothing clashes with a huge threshold.
+    let msdf = outline.generate_msdf(4);
+    let mut corrected = outline.generate_msdf(4);
+    let correction = ErrorCorrection {
+        mode: ErrorCorrectionMode::Indiscriminate,
+        threshold: 1000.0,
+    };
+    correct_errors(&outline, &mut corrected, 4.into(), correction);
+    assert_eq!(wrong_signs(&msdf) == 0, msdf.data == corrected.data);
+}
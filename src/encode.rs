@@ -0,0 +1,174 @@
+use std::{fs, io, path::Path};
+
+use crate::gen::Bitmap;
+
+impl Bitmap<u8> {
+    /// Encodes the bitmap into the binary
+    /// [Netpbm](https://netpbm.sourceforge.net/doc/) format: PGM for one
+    /// channel (SDF), PPM for three channels (MSDF) and PAM for any other
+    /// number of channels (MTSDF).
+    pub fn to_pnm(&self) -> Vec<u8> {
+        let mut pnm = pnm_header(self, u8::MAX as u32);
+        pnm.extend_from_slice(&self.data);
+        pnm
+    }
+
+    /// Saves the bitmap into a Netpbm file, see [`Self::to_pnm`].
+    pub fn save_pnm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_pnm())
+    }
+}
+
+impl Bitmap<u16> {
+    /// Encodes the bitmap into the binary
+    /// [Netpbm](https://netpbm.sourceforge.net/doc/) format with 16-bit
+    /// samples. Channels are encoded the same as in the 8-bit variant.
+    pub fn to_pnm(&self) -> Vec<u8> {
+        let mut pnm = pnm_header(self, u16::MAX as u32);
+        // Netpbm samples are big-endian.
+        pnm.extend(self.data.iter().flat_map(|v| v.to_be_bytes()));
+        pnm
+    }
+
+    /// Saves the bitmap into a Netpbm file, see [`Self::to_pnm`].
+    pub fn save_pnm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_pnm())
+    }
+}
+
+fn pnm_header<T>(bitmap: &Bitmap<T>, max_value: u32) -> Vec<u8> {
+    let (width, height) = (bitmap.width, bitmap.height);
+    let header = match bitmap.channels {
+        1 => format!("P5\n{} {}\n{}\n", width, height, max_value),
+        3 => format!("P6\n{} {}\n{}\n", width, height, max_value),
+        channels => {
+            let tuple_type = match channels {
+                2 => "TUPLTYPE GRAYSCALE_ALPHA\n",
+                4 => "TUPLTYPE RGB_ALPHA\n",
+                _ => "",
+            };
+            format!(
+                "P7\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL {}\n{}ENDHDR\n",
+                width, height, channels, max_value, tuple_type
+            )
+        }
+    };
+    header.into_bytes()
+}
+
+#[cfg(feature = "image")]
+impl Bitmap<u8> {
+    /// Converts the bitmap into a grayscale (SDF), RGB (MSDF) or RGBA
+    /// (MTSDF) image.
+    ///
+    /// Returns `None` if the number of channels isn't 1, 3 or 4.
+    pub fn to_image(&self) -> Option<image::DynamicImage> {
+        use image::{DynamicImage, ImageBuffer};
+
+        let (width, height, data) =
+            (self.width, self.height, self.data.clone());
+        match self.channels {
+            1 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageLuma8),
+            3 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageRgb8),
+            4 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageRgba8),
+            _ => None,
+        }
+    }
+
+    /// Saves the bitmap into a PNG file, see [`Self::to_image`].
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        save_png(self.to_image(), path.as_ref())
+    }
+}
+
+#[cfg(feature = "image")]
+impl Bitmap<u16> {
+    /// Converts the bitmap into a 16-bit grayscale (SDF), RGB (MSDF) or
+    /// RGBA (MTSDF) image.
+    ///
+    /// Returns `None` if the number of channels isn't 1, 3 or 4.
+    pub fn to_image(&self) -> Option<image::DynamicImage> {
+        use image::{DynamicImage, ImageBuffer};
+
+        let (width, height, data) =
+            (self.width, self.height, self.data.clone());
+        match self.channels {
+            1 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageLuma16),
+            3 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageRgb16),
+            4 => ImageBuffer::from_raw(width, height, data)
+                .map(DynamicImage::ImageRgba16),
+            _ => None,
+        }
+    }
+
+    /// Saves the bitmap into a 16-bit PNG file, see [`Self::to_image`].
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        save_png(self.to_image(), path.as_ref())
+    }
+}
+
+#[cfg(feature = "image")]
+fn save_png(
+    image: Option<image::DynamicImage>,
+    path: &Path,
+) -> image::ImageResult<()> {
+    use image::error::{
+        ImageError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind,
+    };
+
+    let image = image.ok_or_else(|| {
+        ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+            ImageFormatHint::Exact(image::ImageFormat::Png),
+            UnsupportedErrorKind::GenericFeature(
+                "bitmap channel count".to_owned(),
+            ),
+        ))
+    })?;
+    image.save_with_format(path, image::ImageFormat::Png)
+}
+
+#[test]
+fn pnm_test() {
+    let bitmap = Bitmap::<u8> {
+        data: vec![0, 64, 128, 255, 1, 2],
+        width: 2,
+        height: 1,
+        channels: 3,
+    };
+    let pnm = bitmap.to_pnm();
+    assert!(pnm.starts_with(b"P6\n2 1\n255\n"));
+    assert!(pnm.ends_with(&bitmap.data));
+
+    let wide = Bitmap::<u16> {
+        data: vec![1, 2, 3, 0xabcd],
+        width: 1,
+        height: 1,
+        channels: 4,
+    };
+    let pam = wide.to_pnm();
+    let header = "P7\nWIDTH 1\nHEIGHT 1\nDEPTH 4\nMAXVAL 65535\n\
+        TUPLTYPE RGB_ALPHA\nENDHDR\n";
+    assert!(pam.starts_with(header.as_bytes()));
+    assert_eq!(pam.len(), header.len() + 8);
+    assert!(pam.ends_with(&[0xab, 0xcd]));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn to_image_test() {
+    let bitmap = Bitmap::<u8> {
+        data: vec![10, 20, 30, 40],
+        width: 2,
+        height: 2,
+        channels: 1,
+    };
+    let image = bitmap.to_image().unwrap().into_luma8();
+    assert_eq!(image.get_pixel(1, 1).0, [40]);
+
+    assert!(Bitmap::<u16>::empty(2).to_image().is_none());
+}
@@ -0,0 +1,67 @@
+use std::{fmt, io};
+
+/// Errors which can occur while loading fonts, building shapes and
+/// generating or exporting atlases.
+///
+/// Segments without length and contours left without any segments are
+/// skipped while building shapes, so valid fonts with such outlines don't
+/// fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The provided data is not a valid font.
+    InvalidFontData,
+    /// The font collection has no face with the requested index.
+    MissingFace(u32),
+    /// The font file couldn't be read.
+    Io(io::ErrorKind),
+    /// The font has no glyph for the requested character.
+    MissingGlyph(char),
+    /// The glyph has no outline to build a shape from.
+    EmptyOutline,
+    /// A new contour was opened or the shape was built while
+    /// the last contour was still open.
+    UnclosedContour,
+    /// A segment was added or a contour was closed without
+    /// opening a contour first.
+    NoOpenContour,
+    /// The shape has no contours.
+    EmptyShape,
+    /// A glyph bitmap doesn't fit into an empty atlas page.
+    AtlasTooSmall,
+    /// The atlas can't be written in the BMFont format, like a name with
+    /// a quote or a value out of range of the binary format.
+    UnsupportedBmFont(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidFontData => write!(f, "invalid font data"),
+            Error::MissingFace(index) => {
+                write!(f, "the font collection has no face {}", index)
+            }
+            Error::Io(kind) => write!(f, "can't read the font file: {}", kind),
+            Error::MissingGlyph(c) => {
+                write!(f, "the font has no glyph for character {:?}", c)
+            }
+            Error::EmptyOutline => write!(f, "the glyph has no outline"),
+            Error::UnclosedContour => {
+                write!(f, "the last contour has not been closed")
+            }
+            Error::NoOpenContour => {
+                write!(f, "open a new contour before adding segments")
+            }
+            Error::EmptyShape => write!(f, "there are no contours"),
+            Error::AtlasTooSmall => {
+                write!(f, "glyph bitmap doesn't fit into an atlas page")
+            }
+            Error::UnsupportedBmFont(reason) => {
+                write!(f, "can't export to BMFont: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
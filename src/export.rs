@@ -0,0 +1,579 @@
+use std::fmt::Write;
+
+use crate::{
+    atlas::{Atlas, AtlasGlyph},
+    error::{Error, Result},
+    font::Font,
+    gen::FieldType,
+};
+
+impl Atlas {
+    /// Returns the atlas layout in the JSON format of
+    /// [msdf-atlas-gen](https://github.com/Chlumsky/msdf-atlas-gen).
+    ///
+    /// Font metrics, plane bounds, advances and kerning are in ems
+    /// (`emSize` is 1) with `y` increasing upwards. Atlas bounds are in
+    /// pixels with `yOrigin` at the bottom of the page saved as an image,
+    /// also for pages flipped by [`YAxis::Up`](crate::YAxis::Up). Both bounds are
+    /// shrunk by half a pixel so that they go through centers of the
+    /// edge pixels, same as in msdf-atlas-gen.
+    ///
+    /// If the atlas has more than one page, every placed glyph also gets
+    /// a `page` index which msdf-atlas-gen doesn't have. Underline metrics
+    /// are not included.
+    ///
+    /// The `font` has to be the one the atlas was generated from.
+    pub fn to_json(&self, font: &Font) -> String {
+        let settings = &self.settings;
+        let em = font.pixels_per_em(settings.scale);
+        let range = settings.range.to_pixels(em);
+        let units_per_em = font.units_per_em() as f32;
+        let v_metrics = font.v_metrics_unscaled();
+        let page_height = settings.page_height as f32;
+        let multi_page = self.pages.len() > 1;
+
+        let mut json = String::new();
+        // Writing into a `String` never fails.
+        let _ = write!(
+            json,
+            "{{\"atlas\":{{\"type\":\"{}\",\"distanceRange\":{},\
+            \"distanceRangeMiddle\":{},\"size\":{},\"width\":{},\"height\":{},\
+            \"yOrigin\":\"bottom\"}},",
+            field_type_name(settings.field_type),
+            range.width(),
+            range.middle(),
+            em,
+            settings.page_width,
+            settings.page_height
+        );
+        let _ = write!(
+            json,
+            "\"metrics\":{{\"emSize\":1,\"lineHeight\":{},\"ascender\":{},\
+            \"descender\":{}}},",
+            (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap)
+                / units_per_em,
+            v_metrics.ascent / units_per_em,
+            v_metrics.descent / units_per_em
+        );
+
+        json.push_str("\"glyphs\":[");
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"unicode\":{},\"advance\":{}",
+                glyph.character as u32,
+                glyph.advance / em
+            );
+            if let Some(placement) = glyph.placement {
+                let plane = placement.plane_bounds;
+                let rect = placement.rect;
+                if multi_page {
+                    let _ = write!(json, ",\"page\":{}", placement.page);
+                }
+                let _ = write!(
+                    json,
+                    ",\"planeBounds\":{{\"left\":{},\"bottom\":{},\
+                    \"right\":{},\"top\":{}}}",
+                    (plane.left + 0.5) / em,
+                    -(plane.bottom - 0.5) / em,
+                    (plane.right - 0.5) / em,
+                    -(plane.top + 0.5) / em
+                );
+                let _ = write!(
+                    json,
+                    ",\"atlasBounds\":{{\"left\":{},\"bottom\":{},\
+                    \"right\":{},\"top\":{}}}",
+                    rect.x as f32 + 0.5,
+                    page_height - (rect.y + rect.height) as f32 + 0.5,
+                    (rect.x + rect.width) as f32 - 0.5,
+                    page_height - rect.y as f32 - 0.5
+                );
+            }
+            json.push('}');
+        }
+        json.push_str("],");
+
+        json.push_str("\"kerning\":[");
+        for (i, (a, b, kerning)) in self.kerning_pairs(font).enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "{{\"unicode1\":{},\"unicode2\":{},\"advance\":{}}}",
+                a as u32,
+                b as u32,
+                kerning / em
+            );
+        }
+        json.push_str("]}");
+
+        json
+    }
+
+    /// Returns the atlas layout in the text format of
+    /// [AngelCode BMFont](https://www.angelcode.com/products/bmfont/).
+    ///
+    /// All values are rounded to whole pixels. `face` is written as the
+    /// font name and `page_files` are file names of the saved pages, in
+    /// order. Pages without a file name are written with an empty one.
+    ///
+    /// The format can't escape names, so [`Error::UnsupportedBmFont`] is
+    /// returned if they contain quotes or line breaks.
+    ///
+    /// The `font` has to be the one the atlas was generated from.
+    pub fn to_bmfont_text(
+        &self,
+        font: &Font,
+        face: &str,
+        page_files: &[&str],
+    ) -> Result<String> {
+        check_bmfont_names(face, page_files, &['"', '\n', '\r'])?;
+        let common = self.bmfont_common(font);
+        let settings = &self.settings;
+        let offset = settings.offset;
+
+        let mut fnt = String::new();
+        let _ = writeln!(
+            fnt,
+            "info face=\"{}\" size={} bold=0 italic=0 charset=\"\" unicode=1 \
+            stretchH=100 smooth=1 aa=1 padding={},{},{},{} spacing={},{}",
+            face,
+            common.size,
+            offset.y.round() as u32,
+            offset.x.round() as u32,
+            offset.y.round() as u32,
+            offset.x.round() as u32,
+            settings.padding,
+            settings.padding
+        );
+        let _ = writeln!(
+            fnt,
+            "common lineHeight={} base={} scaleW={} scaleH={} pages={} \
+            packed=0 alphaChnl=0 redChnl=0 greenChnl=0 blueChnl=0",
+            common.line_height,
+            common.base,
+            settings.page_width,
+            settings.page_height,
+            self.pages.len()
+        );
+        for page in 0..self.pages.len() {
+            let file = page_files.get(page).copied().unwrap_or("");
+            let _ = writeln!(fnt, "page id={} file=\"{}\"", page, file);
+        }
+
+        let _ = writeln!(fnt, "chars count={}", self.glyphs.len());
+        for glyph in &self.glyphs {
+            let c = BmFontChar::new(glyph, common.base);
+            let _ = writeln!(
+                fnt,
+                "char id={} x={} y={} width={} height={} xoffset={} \
+                yoffset={} xadvance={} page={} chnl=15",
+                c.id,
+                c.x,
+                c.y,
+                c.width,
+                c.height,
+                c.x_offset,
+                c.y_offset,
+                c.x_advance,
+                c.page
+            );
+        }
+
+        let kernings = self.bmfont_kernings(font);
+        if !kernings.is_empty() {
+            let _ = writeln!(fnt, "kernings count={}", kernings.len());
+            for (first, second, amount) in kernings {
+                let _ = writeln!(
+                    fnt,
+                    "kerning first={} second={} amount={}",
+                    first, second, amount
+                );
+            }
+        }
+
+        Ok(fnt)
+    }
+
+    /// Returns the atlas layout in the binary format (version 3) of
+    /// [AngelCode BMFont](https://www.angelcode.com/products/bmfont/).
+    ///
+    /// Contains the same information as [`Self::to_bmfont_text`]. Page
+    /// file names are padded with zeros to the same length since the
+    /// format requires it.
+    ///
+    /// Returns [`Error::UnsupportedBmFont`] instead of truncating values
+    /// which don't fit into the fields of the format, like pages larger
+    /// than 65535 pixels, more than 256 pages or offsets and padding over
+    /// 255 pixels. Names can't contain zero bytes.
+    pub fn to_bmfont_binary(
+        &self,
+        font: &Font,
+        face: &str,
+        page_files: &[&str],
+    ) -> Result<Vec<u8>> {
+        check_bmfont_names(face, page_files, &['\0'])?;
+        let common = self.bmfont_common(font);
+        let settings = &self.settings;
+        let offset = settings.offset;
+        let (offset_x, offset_y): (u8, u8) = (
+            narrow(offset.x.round() as i64, "offset over 255 pixels")?,
+            narrow(offset.y.round() as i64, "offset over 255 pixels")?,
+        );
+        let padding: u8 = narrow(settings.padding, "padding over 255 pixels")?;
+        let page_width: u16 =
+            narrow(settings.page_width, "page size over 65535 pixels")?;
+        let page_height: u16 =
+            narrow(settings.page_height, "page size over 65535 pixels")?;
+        // Page indices of chars are bytes.
+        if self.pages.len() > 256 {
+            return Err(Error::UnsupportedBmFont("more than 256 pages"));
+        }
+
+        let mut fnt = b"BMF\x03".to_vec();
+
+        // Info block. Bit field has the `smooth` and `unicode` bits set.
+        let mut block = Vec::new();
+        let size: i16 = narrow(common.size, "font size over 32767 pixels")?;
+        block.extend_from_slice(&size.to_le_bytes());
+        block.push(0b0000_0011);
+        block.push(0);
+        block.extend_from_slice(&100u16.to_le_bytes());
+        block.push(1);
+        block.extend_from_slice(&[offset_y, offset_x, offset_y, offset_x]);
+        block.extend_from_slice(&[padding, padding, 0]);
+        block.extend_from_slice(face.as_bytes());
+        block.push(0);
+        push_block(&mut fnt, 1, &block);
+
+        // Common block.
+        let mut block = Vec::new();
+        let line_height: u16 =
+            narrow(common.line_height, "line height out of range")?;
+        let base: u16 = narrow(common.base, "base out of range")?;
+        block.extend_from_slice(&line_height.to_le_bytes());
+        block.extend_from_slice(&base.to_le_bytes());
+        block.extend_from_slice(&page_width.to_le_bytes());
+        block.extend_from_slice(&page_height.to_le_bytes());
+        block.extend_from_slice(&(self.pages.len() as u16).to_le_bytes());
+        block.extend_from_slice(&[0; 5]);
+        push_block(&mut fnt, 2, &block);
+
+        // Pages block.
+        let name_len = (0..self.pages.len())
+            .map(|page| page_files.get(page).map_or(0, |file| file.len()))
+            .max()
+            .unwrap_or(0);
+        let mut block = Vec::new();
+        for page in 0..self.pages.len() {
+            let file = page_files.get(page).copied().unwrap_or("");
+            block.extend_from_slice(file.as_bytes());
+            block.resize(block.len() + name_len - file.len() + 1, 0);
+        }
+        push_block(&mut fnt, 3, &block);
+
+        // Chars block.
+        let mut block = Vec::new();
+        for glyph in &self.glyphs {
+            let c = BmFontChar::new(glyph, common.base);
+            // Rects are inside pages, so they fit if the page size does.
+            let offsets: [i16; 3] = [
+                narrow(c.x_offset, "char offset out of range")?,
+                narrow(c.y_offset, "char offset out of range")?,
+                narrow(c.x_advance, "char advance out of range")?,
+            ];
+            block.extend_from_slice(&c.id.to_le_bytes());
+            for value in [c.x, c.y, c.width, c.height] {
+                block.extend_from_slice(&(value as u16).to_le_bytes());
+            }
+            for value in offsets {
+                block.extend_from_slice(&value.to_le_bytes());
+            }
+            block.push(c.page as u8);
+            block.push(15);
+        }
+        push_block(&mut fnt, 4, &block);
+
+        // Kerning block is left out if there are no kerning pairs.
+        let kernings = self.bmfont_kernings(font);
+        if !kernings.is_empty() {
+            let mut block = Vec::new();
+            for (first, second, amount) in kernings {
+                let amount: i16 = narrow(amount, "kerning out of range")?;
+                block.extend_from_slice(&first.to_le_bytes());
+                block.extend_from_slice(&second.to_le_bytes());
+                block.extend_from_slice(&amount.to_le_bytes());
+            }
+            push_block(&mut fnt, 5, &block);
+        }
+
+        Ok(fnt)
+    }
+
+    /// Returns kerning in pixels for every pair of atlas characters which
+    /// have non-zero kerning.
+    fn kerning_pairs<'a>(
+        &'a self,
+        font: &'a Font,
+    ) -> impl Iterator<Item = (char, char, f32)> + 'a {
+        let charset = self.glyphs.iter().map(|glyph| glyph.character);
+        font.kerning_pairs(self.settings.scale, charset)
+    }
+
+    /// Kerning pairs with amounts rounded to whole pixels.
+    fn bmfont_kernings(&self, font: &Font) -> Vec<(u32, u32, i32)> {
+        self.kerning_pairs(font)
+            .map(|(a, b, kerning)| (a as u32, b as u32, kerning.round() as i32))
+            .filter(|&(_, _, amount)| amount != 0)
+            .collect()
+    }
+
+    fn bmfont_common(&self, font: &Font) -> BmFontCommon {
+        let v_metrics = font.v_metrics(self.settings.scale);
+        BmFontCommon {
+            size: font.pixels_per_em(self.settings.scale).round() as i32,
+            line_height: (v_metrics.ascent - v_metrics.descent
+                + v_metrics.line_gap)
+                .round() as i32,
+            base: v_metrics.ascent.round() as i32,
+        }
+    }
+}
+
+/// Values shared by the `info` and `common` BMFont blocks.
+struct BmFontCommon {
+    /// Em size in pixels.
+    size: i32,
+    line_height: i32,
+    /// Distance from the top of the line to the baseline.
+    base: i32,
+}
+
+/// A single BMFont character entry in pixels.
+struct BmFontChar {
+    id: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    x_offset: i32,
+    /// Distance from the top of the line to the top of the bitmap.
+    y_offset: i32,
+    x_advance: i32,
+    page: usize,
+}
+
+impl BmFontChar {
+    fn new(glyph: &AtlasGlyph, base: i32) -> Self {
+        let x_advance = glyph.advance.round() as i32;
+        match glyph.placement {
+            Some(placement) => Self {
+                id: glyph.character as u32,
+                x: placement.rect.x,
+                y: placement.rect.y,
+                width: placement.rect.width,
+                height: placement.rect.height,
+                x_offset: placement.plane_bounds.left.round() as i32,
+                y_offset: base + placement.plane_bounds.top.round() as i32,
+                x_advance,
+                page: placement.page,
+            },
+            None => Self {
+                id: glyph.character as u32,
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+                x_offset: 0,
+                y_offset: 0,
+                x_advance,
+                page: 0,
+            },
+        }
+    }
+}
+
+/// Checks that the face and page file names don't contain any of the
+/// characters which would break the BMFont file.
+fn check_bmfont_names(
+    face: &str,
+    page_files: &[&str],
+    forbidden: &[char],
+) -> Result<()> {
+    let names = std::iter::once(face).chain(page_files.iter().copied());
+    for name in names {
+        if name.contains(forbidden) {
+            return Err(Error::UnsupportedBmFont(
+                "names contain characters the format can't store",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Converts a value into a narrower field of the binary BMFont format.
+fn narrow<T, V>(value: V, reason: &'static str) -> Result<T>
+where
+    T: TryFrom<V>,
+{
+    T::try_from(value).map_err(|_| Error::UnsupportedBmFont(reason))
+}
+
+/// Appends a binary BMFont block with its type and size.
+fn push_block(fnt: &mut Vec<u8>, block_type: u8, block: &[u8]) {
+    fnt.push(block_type);
+    fnt.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    fnt.extend_from_slice(block);
+}
+
+fn field_type_name(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::Sdf => "sdf",
+        FieldType::PseudoSdf => "psdf",
+        FieldType::Msdf => "msdf",
+        FieldType::Mtsdf => "mtsdf",
+    }
+}
+
+#[test]
+fn json_export_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let atlas =
+        Atlas::generate(&font, "A V".chars(), Default::default()).unwrap();
+    let json = atlas.to_json(&font);
+
+    assert!(json.starts_with("{\"atlas\":{\"type\":\"msdf\""));
+    assert!(json.contains("\"yOrigin\":\"bottom\""));
+    assert!(json.contains("\"metrics\":{\"emSize\":1,"));
+    // Space has no bounds.
+    assert!(json.contains("{\"unicode\":32,\"advance\":"));
+    assert!(!json.contains("\"page\""));
+    assert_eq!(json.matches("\"planeBounds\"").count(), 2);
+    assert_eq!(json.matches("\"atlasBounds\"").count(), 2);
+    // Braces and brackets are balanced.
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+    assert_eq!(json.matches('[').count(), json.matches(']').count());
+
+    // Bounds point at the flipped glyph in pages saved as images.
+    let up_settings = crate::atlas::AtlasSettings {
+        y_axis: crate::YAxis::Up,
+        ..Default::default()
+    };
+    let up = Atlas::generate(&font, "A V".chars(), up_settings).unwrap();
+    let rect = atlas.glyph('A').unwrap().placement.unwrap().rect;
+    let bounds = format!(
+        "\"atlasBounds\":{{\"left\":{},\"bottom\":{},",
+        rect.x as f32 + 0.5,
+        rect.y as f32 + 0.5
+    );
+    assert!(up.to_json(&font).contains(&bounds));
+}
+
+#[test]
+fn bmfont_export_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let atlas =
+        Atlas::generate(&font, "A V".chars(), Default::default()).unwrap();
+
+    let text = atlas
+        .to_bmfont_text(&font, "Roboto", &["roboto_0.png"])
+        .unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("info face=\"Roboto\" size="));
+    assert!(lines[1].starts_with("common lineHeight="));
+    assert_eq!(lines[2], "page id=0 file=\"roboto_0.png\"");
+    assert_eq!(lines[3], "chars count=3");
+    assert!(lines[5].starts_with("char id=32 x=0 y=0 width=0 height=0"));
+
+    let binary = atlas
+        .to_bmfont_binary(&font, "Roboto", &["roboto_0.png"])
+        .unwrap();
+    assert_eq!(&binary[..4], b"BMF\x03");
+    // Walk through the blocks and check the chars block size.
+    let mut i = 4;
+    let mut chars_size = 0;
+    while i < binary.len() {
+        let size = u32::from_le_bytes(binary[i + 1..i + 5].try_into().unwrap());
+        if binary[i] == 4 {
+            chars_size = size;
+        }
+        i += 5 + size as usize;
+    }
+    assert_eq!(i, binary.len());
+    assert_eq!(chars_size, 3 * 20);
+
+    // Names which would break the file and values out of range are errors
+    // instead of corrupt files.
+    let quoted = atlas.to_bmfont_text(&font, "Robo\"to", &[]);
+    assert!(matches!(quoted, Err(Error::UnsupportedBmFont(_))));
+    let newline = atlas.to_bmfont_text(&font, "Roboto", &["a\nb.png"]);
+    assert!(matches!(newline, Err(Error::UnsupportedBmFont(_))));
+    let zero = atlas.to_bmfont_binary(&font, "Robo\0to", &[]);
+    assert!(matches!(zero, Err(Error::UnsupportedBmFont(_))));
+
+    let mut atlas = atlas;
+    let settings = atlas.settings;
+    atlas.settings.page_width = 70000;
+    let result = atlas.to_bmfont_binary(&font, "Roboto", &[]);
+    assert!(matches!(result, Err(Error::UnsupportedBmFont(_))));
+    assert!(atlas.to_bmfont_text(&font, "Roboto", &[]).is_ok());
+    atlas.settings = settings;
+
+    atlas.settings.offset = crate::Offset::uniform(300.0);
+    let result = atlas.to_bmfont_binary(&font, "Roboto", &[]);
+    assert!(matches!(result, Err(Error::UnsupportedBmFont(_))));
+    atlas.settings = settings;
+
+    let page = atlas.pages[0].clone();
+    atlas.pages.resize(257, page);
+    let result = atlas.to_bmfont_binary(&font, "Roboto", &[]);
+    assert!(matches!(result, Err(Error::UnsupportedBmFont(_))));
+    atlas.pages.truncate(256);
+    assert!(atlas.to_bmfont_binary(&font, "Roboto", &[]).is_ok());
+}
+
+#[test]
+fn kerning_export_test() {
+    use crate::font::with_kern_table;
+
+    let roboto = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let plain = Font::from_slice(roboto).unwrap();
+    let (a, v) = (plain.glyph('A').id(), plain.glyph('V').id());
+    let font =
+        Font::from_vec(with_kern_table(roboto, &[(a, v, -400)])).unwrap();
+    let atlas =
+        Atlas::generate(&font, "AV".chars(), Default::default()).unwrap();
+    let scale = atlas.settings.scale;
+    let kerning = font.pair_kerning(scale, 'A', 'V');
+    let amount = kerning.round() as i32;
+    assert!(amount < 0);
+
+    let json = atlas.to_json(&font);
+    let pair = format!(
+        "\"kerning\":[{{\"unicode1\":65,\"unicode2\":86,\"advance\":{}}}]",
+        kerning / font.pixels_per_em(scale)
+    );
+    assert!(json.ends_with(&format!("{}}}", pair)));
+
+    let text = atlas.to_bmfont_text(&font, "Roboto", &[]).unwrap();
+    let kernings = format!(
+        "kernings count=1\nkerning first=65 second=86 amount={}\n",
+        amount
+    );
+    assert!(text.ends_with(&kernings));
+
+    // The kerning block is the last one.
+    let binary = atlas.to_bmfont_binary(&font, "Roboto", &[]).unwrap();
+    let mut block = vec![5];
+    block.extend(10u32.to_le_bytes());
+    block.extend(65u32.to_le_bytes());
+    block.extend(86u32.to_le_bytes());
+    block.extend((amount as i16).to_le_bytes());
+    assert!(binary.ends_with(&block));
+}
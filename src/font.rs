@@ -1,18 +1,125 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::Arc,
+};
 
-use rusttype::{Font as RTFont, Glyph as RTGlyph, Rect, Scale, VMetrics};
+use rusttype::{
+    Font as RTFont, Glyph as RTGlyph, GlyphId, HMetrics, IntoGlyphId, Rect,
+    Scale, VMetrics,
+};
+use ttf_parser::{name_id, Tag};
 
-use crate::{gen::Bitmap, path::PathBuilder, shape::Shape, vector::Vector2};
+use crate::{
+    coloring::{self, ColoringStrategy, EdgeColor},
+    config::{GeneratorConfig, OutputBitmap},
+    correction::ErrorCorrection,
+    error::{Error, Result},
+    gen::{Bitmap, FieldType, FloatBitmap},
+    path::PathBuilder,
+    range::Range,
+    shape::{FillRule, Shape},
+    vector::Vector2,
+};
 
+/// Font loaded from borrowed data with [`Font::from_slice`] or from owned
+/// data with [`Font::from_vec`] and [`Font::from_file`].
+///
+/// Font collections (`.ttc` and `.otc` files) contain multiple faces. The
+/// constructors above load the first one, use [`Font::faces`] to list them
+/// and [`Font::from_collection`] to pick one.
+///
+/// Cloning is cheap since the font data is shared. Owned fonts are
+/// `Font<'static>` and can be stored or sent across threads freely.
+#[derive(Clone)]
 pub struct Font<'a> {
     inner: Arc<RTFont<'a>>,
+    /// Raw `cmap` and `kern` tables since rusttype can't iterate them.
+    cmap: Arc<[u8]>,
+    kern: Arc<[u8]>,
+}
+
+impl Font<'static> {
+    /// Loads the font from owned data.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        let cmap = raw_table(&data, 0, b"cmap");
+        let kern = raw_table(&data, 0, b"kern");
+        let face = RTFont::try_from_vec(data).ok_or(Error::InvalidFontData)?;
+        Ok(Self {
+            inner: Arc::new(face),
+            cmap,
+            kern,
+        })
+    }
+
+    /// Reads the whole font file and loads the font from it.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read(path).map_err(|err| Error::Io(err.kind()))?;
+        Self::from_vec(data)
+    }
+
+    /// Loads the face with the provided index from owned data of a font
+    /// collection, see [`Font::from_collection`].
+    pub fn from_collection_vec(data: Vec<u8>, index: u32) -> Result<Self> {
+        check_face_index(&data, index)?;
+        let cmap = raw_table(&data, index, b"cmap");
+        let kern = raw_table(&data, index, b"kern");
+        let face = RTFont::try_from_vec_and_index(data, index)
+            .ok_or(Error::InvalidFontData)?;
+        Ok(Self {
+            inner: Arc::new(face),
+            cmap,
+            kern,
+        })
+    }
 }
 
 impl<'a> Font<'a> {
-    pub fn from_slice(data: &'a [u8]) -> Self {
-        // TODO add custom errors for results
-        let face = Arc::new(RTFont::try_from_bytes(data).unwrap());
-        Self { inner: face }
+    pub fn from_slice(data: &'a [u8]) -> Result<Self> {
+        let face =
+            RTFont::try_from_bytes(data).ok_or(Error::InvalidFontData)?;
+        Ok(Self {
+            inner: Arc::new(face),
+            cmap: raw_table(data, 0, b"cmap"),
+            kern: raw_table(data, 0, b"kern"),
+        })
+    }
+
+    /// Loads the face with the provided index from a font collection.
+    ///
+    /// Single fonts only have the face `0`. Returns [`Error::MissingFace`]
+    /// if the index is out of range.
+    pub fn from_collection(data: &'a [u8], index: u32) -> Result<Self> {
+        check_face_index(data, index)?;
+        let face = RTFont::try_from_bytes_and_index(data, index)
+            .ok_or(Error::InvalidFontData)?;
+        Ok(Self {
+            inner: Arc::new(face),
+            cmap: raw_table(data, index, b"cmap"),
+            kern: raw_table(data, index, b"kern"),
+        })
+    }
+
+    /// Returns the number of faces in the font data, `1` if it isn't a
+    /// font collection.
+    pub fn face_count(data: &[u8]) -> u32 {
+        ttf_parser::fonts_in_collection(data).unwrap_or(1)
+    }
+
+    /// Returns an iterator over names of all valid faces in the font data,
+    /// for picking the index for [`Font::from_collection`].
+    pub fn faces(data: &[u8]) -> impl Iterator<Item = FaceInfo> + '_ {
+        (0..Self::face_count(data)).filter_map(move |index| {
+            let face = ttf_parser::Face::from_slice(data, index).ok()?;
+            Some(FaceInfo {
+                index,
+                family_name: face_name(&face, name_id::FAMILY),
+                style_name: face_name(&face, name_id::TYPOGRAPHIC_SUBFAMILY)
+                    .or_else(|| face_name(&face, name_id::SUBFAMILY)),
+                post_script_name: face_name(&face, name_id::POST_SCRIPT_NAME),
+            })
+        })
     }
 
     pub fn glyph_count(&self) -> usize {
@@ -31,13 +138,195 @@ impl<'a> Font<'a> {
         self.inner.v_metrics_unscaled()
     }
 
-    // TODO maybe use IntoGlyphId
-    pub fn glyph<C: Into<char>>(&self, id: C) -> Glyph<'a> {
-        let glyph = self.inner.glyph(id.into());
-        // let font = Arc::clone(&self.inner);
+    /// Returns the size of the em square in pixels at the provided scale.
+    ///
+    /// The [`Scale`] is the height from the descender to the ascender and
+    /// not the em size.
+    pub fn pixels_per_em(&self, scale: Scale) -> f32 {
+        pixels_per_em(&self.inner, scale)
+    }
+
+    /// Returns the kerning in pixels which should be added to the advance
+    /// of `a` when it is followed by `b`.
+    ///
+    /// Only the `kern` table is supported. Kerning stored in the `GPOS`
+    /// table is not applied and results in zero.
+    pub fn pair_kerning(&self, scale: Scale, a: char, b: char) -> f32 {
+        self.inner.pair_kerning(scale, a, b)
+    }
+
+    /// Returns an iterator over all pairs of characters from the charset
+    /// with non-zero kerning, as `(a, b, kerning)`.
+    ///
+    /// See [`Self::pair_kerning`]. Repeated characters are ignored. Pairs
+    /// come in the charset order and only pairs listed in the `kern` table
+    /// are looked up, unless it has class based subtables.
+    pub fn kerning_pairs<I>(
+        &self,
+        scale: Scale,
+        charset: I,
+    ) -> impl Iterator<Item = (char, char, f32)> + '_
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut seen = HashSet::new();
+        let chars = charset
+            .into_iter()
+            .filter(|&c| seen.insert(c))
+            .collect::<Vec<_>>();
+        let count = chars.len();
+
+        // Pairs of indices into `chars`.
+        let mut pairs = Vec::new();
+        match self.kern_glyph_pairs() {
+            Some(glyph_pairs) => {
+                let mut indices = HashMap::<_, Vec<_>>::new();
+                for (i, &c) in chars.iter().enumerate() {
+                    indices.entry(self.glyph(c).id()).or_default().push(i);
+                }
+                for (left, right) in glyph_pairs {
+                    if let (Some(a), Some(b)) =
+                        (indices.get(&left), indices.get(&right))
+                    {
+                        for &a in a {
+                            pairs.extend(b.iter().map(|&b| (a, b)));
+                        }
+                    }
+                }
+                pairs.sort_unstable();
+                pairs.dedup();
+            }
+            None => {
+                pairs.extend((0..count * count).map(|i| (i / count, i % count)))
+            }
+        }
+
+        pairs.into_iter().filter_map(move |(a, b)| {
+            let (a, b) = (chars[a], chars[b]);
+            let kerning = self.pair_kerning(scale, a, b);
+            (kerning != 0.0).then_some((a, b, kerning))
+        })
+    }
+
+    /// Returns glyph id pairs listed in the horizontal `kern` subtables,
+    /// or `None` if some of them are class based and can't be listed.
+    fn kern_glyph_pairs(&self) -> Option<Vec<(u16, u16)>> {
+        use ttf_parser::kern::{Format, Table};
+
+        let mut pairs = Vec::new();
+        let table = match Table::parse(&self.kern) {
+            Some(table) => table,
+            None => return Some(pairs),
+        };
+        for subtable in table.subtables {
+            if !subtable.horizontal || subtable.variable {
+                continue;
+            }
+            match subtable.format {
+                Format::Format0(subtable) => pairs.extend(
+                    subtable
+                        .pairs
+                        .into_iter()
+                        .map(|pair| (pair.left().0, pair.right().0)),
+                ),
+                Format::Format2(_) | Format::Format3(_) => return None,
+                // State machines aren't applied by `pair_kerning` either.
+                _ => {}
+            }
+        }
+        Some(pairs)
+    }
+
+    /// Returns the glyph for a character or a [`GlyphId`], like the ids
+    /// produced by text shaping.
+    ///
+    /// Characters missing from the font and ids out of range give the
+    /// `.notdef` glyph with id `0`.
+    pub fn glyph<C: IntoGlyphId>(&self, id: C) -> Glyph<'a> {
+        let glyph = self.inner.glyph(id);
 
         Glyph { inner: glyph }
     }
+
+    /// Returns an iterator over ids of all glyphs in the font, starting
+    /// with the `.notdef` glyph.
+    pub fn glyph_ids(&self) -> impl Iterator<Item = GlyphId> {
+        (0..self.glyph_count() as u16).map(GlyphId)
+    }
+
+    /// Returns an iterator over all characters mapped by the `cmap` table
+    /// to a glyph, as `(char, glyph id)` in the codepoint order.
+    ///
+    /// Codepoints are collected from all Unicode subtables, each is mapped
+    /// to the same glyph as [`Self::glyph`] would give.
+    pub fn char_mappings(&self) -> impl Iterator<Item = (char, GlyphId)> + '_ {
+        let mut codepoints = Vec::new();
+        if let Some(table) = ttf_parser::cmap::Table::parse(&self.cmap) {
+            for subtable in table.subtables {
+                if subtable.is_unicode() {
+                    subtable.codepoints(|c| codepoints.push(c));
+                }
+            }
+        }
+        codepoints.sort_unstable();
+        codepoints.dedup();
+
+        codepoints
+            .into_iter()
+            .filter_map(char::from_u32)
+            .filter_map(|c| {
+                let id = self.inner.glyph(c).id();
+                (id.0 != 0).then_some((c, id))
+            })
+    }
+
+    /// Same as [`Self::glyph`] but returns [`Error::MissingGlyph`] if the
+    /// font has no glyph for the character instead of the `.notdef` glyph.
+    pub fn try_glyph<C: Into<char>>(&self, c: C) -> Result<Glyph<'a>> {
+        let c = c.into();
+        let glyph = self.glyph(c);
+        if glyph.id() == 0 {
+            return Err(Error::MissingGlyph(c));
+        }
+        Ok(glyph)
+    }
+}
+
+/// Names of a face in the font data, see [`Font::faces`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaceInfo {
+    /// Index of the face for [`Font::from_collection`].
+    pub index: u32,
+    /// Family name like `Noto Sans CJK JP`.
+    pub family_name: Option<String>,
+    /// Style of the face in the family like `Bold` or `Italic`.
+    pub style_name: Option<String>,
+    /// PostScript name like `NotoSansCJKjp-Bold`.
+    pub post_script_name: Option<String>,
+}
+
+fn check_face_index(data: &[u8], index: u32) -> Result<()> {
+    if index >= Font::face_count(data) {
+        return Err(Error::MissingFace(index));
+    }
+    Ok(())
+}
+
+/// Returns the raw table of the face, empty if it's missing.
+fn raw_table(data: &[u8], index: u32, tag: &[u8; 4]) -> Arc<[u8]> {
+    ttf_parser::RawFace::from_slice(data, index)
+        .ok()
+        .and_then(|face| face.table(Tag::from_bytes(tag)))
+        .unwrap_or_default()
+        .into()
+}
+
+/// Returns the first Unicode name record with the provided id.
+fn face_name(face: &ttf_parser::Face, id: u16) -> Option<String> {
+    face.names()
+        .into_iter()
+        .find(|name| name.name_id == id && name.is_unicode())
+        .and_then(|name| name.to_string())
 }
 
 pub struct Glyph<'font> {
@@ -50,6 +339,12 @@ impl Glyph<'_> {
         self.inner.id().0
     }
 
+    /// Returns the advance width and left side bearing in pixels at the
+    /// provided scale.
+    pub fn h_metrics(&self, scale: Scale) -> HMetrics {
+        self.inner.clone().scaled(scale).h_metrics()
+    }
+
     /// Builds a [`GlyphOutline`] with the provided [`Scale`] and [`Offset`].
     ///
     /// Scale is automatically normalized by the `units_per_em` factor.
@@ -57,31 +352,71 @@ impl Glyph<'_> {
     /// Offset is mainly used in the sdf generation process for better view
     /// of the glyph. It adds empty space to the left, right, top or bottom
     /// of the outline .
-    pub fn build(self, scale: Scale, offset: Offset) -> GlyphOutline {
+    ///
+    /// Glyphs without an outline (space, control characters...) are built
+    /// into an empty outline (see [`GlyphOutline::is_empty`]) which still
+    /// carries the horizontal metrics. Returns [`Error::EmptyOutline`] if
+    /// the glyph has a bounding box but the outline couldn't be built.
+    pub fn build(self, scale: Scale, offset: Offset) -> Result<GlyphOutline> {
         // Offset the shape to the right and the bottom
         let pos = rusttype::Point {
             x: offset.x,
             y: offset.y,
         };
-        let glyph = self.inner.scaled(scale).positioned(pos);
+        let pixels_per_em = pixels_per_em(self.inner.font(), scale);
+        let scaled = self.inner.scaled(scale);
+        let h_metrics = scaled.h_metrics();
+        let glyph = scaled.positioned(pos);
         let mut builder = PathBuilder::new(offset);
 
-        let bbox = BBox::from(glyph.pixel_bounding_box().unwrap());
-        dbg!(bbox);
+        let bbox = match glyph.pixel_bounding_box() {
+            Some(bbox) => BBox::from(bbox),
+            None => {
+                let mut outline = GlyphOutline::empty(h_metrics, offset);
+                outline.pixels_per_em = pixels_per_em;
+                return Ok(outline);
+            }
+        };
 
-        let result = glyph.build_outline(&mut builder);
-        assert!(result, "Glyph outline error!");
+        if !glyph.build_outline(&mut builder) {
+            return Err(Error::EmptyOutline);
+        }
 
-        let shape = builder.build_shape();
+        let shape = builder.build_shape()?;
 
-        GlyphOutline::from_shape(shape, bbox, offset)
+        let mut outline = GlyphOutline::from_shape(shape, bbox, offset);
+        outline.h_metrics = h_metrics;
+        outline.pixels_per_em = pixels_per_em;
+        // Both the glyph position and the path builder add the offset.
+        outline.origin = Vector2::new(
+            2.0 * offset.x - bbox.tl.x as f32,
+            2.0 * offset.y - bbox.tl.y as f32,
+        );
+        Ok(outline)
     }
 }
 
+/// See [`Font::pixels_per_em`].
+fn pixels_per_em(font: &RTFont, scale: Scale) -> f32 {
+    let v_metrics = font.v_metrics_unscaled();
+    scale.y * font.units_per_em() as f32
+        / (v_metrics.ascent - v_metrics.descent)
+}
+
 pub struct GlyphOutline {
     pub(crate) bbox: BBox,
     pub(crate) shape: Shape,
     pub(crate) offset: Offset,
+    pub(crate) h_metrics: HMetrics,
+    /// Position of the glyph origin in the generated bitmap.
+    pub(crate) origin: Vector2<f32>,
+    /// Size of the em square in pixels, used for ranges in ems.
+    pub(crate) pixels_per_em: f32,
+    /// Colored shape before the overlap correction, only kept if the
+    /// correction changed it.
+    pub(crate) overlapping: Option<Shape>,
+    /// Strategy and angle threshold used for coloring the shape.
+    pub(crate) coloring: (ColoringStrategy, f32),
 }
 
 impl GlyphOutline {
@@ -89,22 +424,251 @@ impl GlyphOutline {
     /// bounding box.
     ///
     /// Use the [`Self::generate`] functions to create a distance field bitmap.
-    pub fn from_shape(shape: Shape, bbox: BBox, offset: Offset) -> Self {
+    ///
+    /// Overlapping contours are merged first so the distance field matches
+    /// the non-zero fill of the shape. The generation can still use the
+    /// original contours, see [`GeneratorConfig::overlap_correction`].
+    ///
+    /// Segments of the shape get colored for the multi-channel generation
+    /// using [`ColoringStrategy::Simple`]. Use [`Self::color_edges`] to
+    /// choose a different strategy.
+    ///
+    /// Since custom shapes have no font metrics, the advance width is set
+    /// to the width of the bounding box and one em is one pixel.
+    pub fn from_shape(mut shape: Shape, bbox: BBox, offset: Offset) -> Self {
+        let mut overlapping = shape.clone();
+        let changed = shape.correct_overlaps(FillRule::NonZero);
+        let angle_threshold = coloring::DEFAULT_ANGLE_THRESHOLD;
+        coloring::color_simple(&mut shape, angle_threshold);
+        let overlapping = changed.then(|| {
+            coloring::color_simple(&mut overlapping, angle_threshold);
+            overlapping
+        });
+
+        let h_metrics = HMetrics {
+            advance_width: bbox.width() as f32,
+            left_side_bearing: 0.0,
+        };
         Self {
             bbox,
             shape,
             offset,
+            h_metrics,
+            origin: Vector2::new(offset.x, offset.y),
+            pixels_per_em: 1.0,
+            overlapping,
+            coloring: (ColoringStrategy::Simple, angle_threshold),
+        }
+    }
+
+    /// Initialize a [`GlyphOutline`] without any contours, used for glyphs
+    /// like space which only have metrics.
+    ///
+    /// All generated bitmaps of an empty outline have zero size.
+    pub fn empty(h_metrics: HMetrics, offset: Offset) -> Self {
+        Self {
+            bbox: BBox::new(Vector2::ZERO_I32, Vector2::ZERO_I32),
+            shape: Shape::new(Vec::new()),
+            offset,
+            h_metrics,
+            origin: Vector2::new(offset.x, offset.y),
+            pixels_per_em: 1.0,
+            overlapping: None,
+            coloring: (
+                ColoringStrategy::Simple,
+                coloring::DEFAULT_ANGLE_THRESHOLD,
+            ),
+        }
+    }
+
+    /// Checks if the outline has no contours.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shape.contours.is_empty()
+    }
+
+    /// Returns the horizontal metrics of the glyph.
+    #[inline]
+    pub fn h_metrics(&self) -> HMetrics {
+        self.h_metrics
+    }
+
+    /// Returns the size of the em square in pixels at the scale the glyph
+    /// was built with.
+    #[inline]
+    pub fn pixels_per_em(&self) -> f32 {
+        self.pixels_per_em
+    }
+
+    /// Converts the range to pixels with the em size of this outline.
+    #[inline]
+    pub(crate) fn pixel_range(&self, range: Range) -> Range {
+        range.to_pixels(self.pixels_per_em)
+    }
+
+    /// Returns the position of the glyph origin (the point on the baseline
+    /// where the pen is placed) in pixel coordinates of the generated
+    /// bitmap.
+    #[inline]
+    pub fn origin(&self) -> Vector2<f32> {
+        self.origin
+    }
+
+    /// Recolors segments of the shape with the provided strategy.
+    ///
+    /// Joints of two segments are considered as corners if the angle
+    /// between them is bigger than `angle_threshold` (radians). Use
+    /// [`DEFAULT_ANGLE_THRESHOLD`](coloring::DEFAULT_ANGLE_THRESHOLD) if not sure.
+    pub fn color_edges(
+        &mut self,
+        strategy: ColoringStrategy,
+        angle_threshold: f32,
+    ) {
+        coloring::color_edges(&mut self.shape, strategy, angle_threshold);
+        if let Some(overlapping) = &mut self.overlapping {
+            coloring::color_edges(overlapping, strategy, angle_threshold);
+        }
+        self.coloring = (strategy, angle_threshold);
+    }
+
+    /// Returns edge colors of segments for each contour of the shape.
+    ///
+    /// Mainly used for inspecting colorings when debugging artifacts
+    /// of multi-channel distance fields.
+    pub fn edge_colors(&self) -> Vec<&[EdgeColor]> {
+        self.shape
+            .contours
+            .iter()
+            .map(|contour| contour.colors())
+            .collect()
+    }
+
+    /// Generates a distance field with all settings of the config.
+    ///
+    /// Passes run in the order: overlap correction, generation, sign
+    /// correction, error correction and flipping of the y axis. The result
+    /// is then converted into the configured output format.
+    pub fn generate(&self, config: &GeneratorConfig) -> OutputBitmap {
+        match self.shape_for(config) {
+            Some(shape) => {
+                crate::config::generate(&self.with_shape(shape), config)
+            }
+            None => crate::config::generate(self, config),
+        }
+    }
+
+    /// Returns the shape matching the overlap correction and fill rule of
+    /// the config or `None` if the current shape matches.
+    fn shape_for(&self, config: &GeneratorConfig) -> Option<Shape> {
+        let overlapping = self.overlapping.as_ref()?;
+        if !config.overlap_correction {
+            return Some(overlapping.clone());
+        }
+        match config.fill_rule {
+            FillRule::NonZero => None,
+            fill_rule => {
+                let mut shape = overlapping.clone();
+                shape.correct_overlaps(fill_rule);
+                let (strategy, angle_threshold) = self.coloring;
+                coloring::color_edges(&mut shape, strategy, angle_threshold);
+                Some(shape)
+            }
+        }
+    }
+
+    fn with_shape(&self, shape: Shape) -> Self {
+        Self {
+            shape,
+            overlapping: None,
+            ..*self
         }
     }
 
     /// Returns a image bitmap with signed distance fields.
-    pub fn generate_sdf(&self, range: usize) -> Bitmap {
-        crate::gen::gen_sdf(self, range)
+    pub fn generate_sdf(&self, range: impl Into<Range>) -> Bitmap {
+        crate::gen::gen_sdf(self, range.into())
     }
 
     /// Returns a image bitmap with pseudo signed distance fields.
-    pub fn generate_pseudo_sdf(&self, range: usize) -> Bitmap {
-        crate::gen::gen_pseudo_sdf(self, range)
+    pub fn generate_pseudo_sdf(&self, range: impl Into<Range>) -> Bitmap {
+        crate::gen::gen_pseudo_sdf(self, range.into())
+    }
+
+    /// Returns a image bitmap with multi-channel signed distance fields.
+    ///
+    /// Each pixel consists of three (RGB) channels and the distance is
+    /// reconstructed by taking the median of the three.
+    pub fn generate_msdf(&self, range: impl Into<Range>) -> Bitmap {
+        crate::gen::gen_msdf(self, range.into())
+    }
+
+    /// Returns a image bitmap with multi-channel and true signed distance
+    /// fields.
+    ///
+    /// Each pixel consists of four (RGBA) channels where RGB channels hold
+    /// the multi-channel distance and the alpha channel holds the true
+    /// signed distance.
+    pub fn generate_mtsdf(&self, range: impl Into<Range>) -> Bitmap {
+        crate::gen::gen_mtsdf(self, range.into())
+    }
+
+    /// Returns a 16-bit image bitmap with signed distance fields.
+    pub fn generate_sdf_u16(&self, range: impl Into<Range>) -> Bitmap<u16> {
+        crate::gen::gen_u16(self, FieldType::Sdf, range.into())
+    }
+
+    /// Returns a 16-bit image bitmap with multi-channel signed distance
+    /// fields.
+    pub fn generate_msdf_u16(&self, range: impl Into<Range>) -> Bitmap<u16> {
+        crate::gen::gen_u16(self, FieldType::Msdf, range.into())
+    }
+
+    /// Returns a 16-bit image bitmap with multi-channel and true signed
+    /// distance fields.
+    pub fn generate_mtsdf_u16(&self, range: impl Into<Range>) -> Bitmap<u16> {
+        crate::gen::gen_u16(self, FieldType::Mtsdf, range.into())
+    }
+
+    /// Returns a bitmap of the provided distance field type holding raw
+    /// signed distances in pixels.
+    ///
+    /// Convert it with [`FloatBitmap::to_u8`], [`FloatBitmap::to_u16`] or
+    /// [`FloatBitmap::to_em`].
+    pub fn generate_float(&self, field_type: FieldType) -> FloatBitmap {
+        crate::gen::gen_float(self, field_type)
+    }
+
+    /// Flips the sign of bitmap pixels which are on the wrong side of the
+    /// outline according to the scanline fill of the shape.
+    ///
+    /// Useful for shapes with inconsistent winding or for filling them by
+    /// the even-odd rule. The bitmap has to be generated from this outline
+    /// with the provided range.
+    pub fn correct_sign(
+        &self,
+        bitmap: &mut Bitmap,
+        range: impl Into<Range>,
+        fill_rule: FillRule,
+    ) {
+        crate::gen::correct_sign(self, bitmap, range.into(), fill_rule)
+    }
+
+    /// Removes clashing-channel artifacts from a multi-channel bitmap
+    /// generated from this outline with the provided range.
+    ///
+    /// See [`ErrorCorrection`] for the available settings.
+    pub fn correct_errors(
+        &self,
+        bitmap: &mut Bitmap,
+        range: impl Into<Range>,
+        correction: ErrorCorrection,
+    ) {
+        crate::correction::correct_errors(
+            self,
+            bitmap,
+            range.into(),
+            correction,
+        )
     }
 
     /// Returns the width of the shape's bounding box.
@@ -120,13 +684,24 @@ impl GlyphOutline {
     }
 }
 
-/// `Bounding box` represents an imaginary rectangle.
+/// `Bounding box` represents an imaginary rectangle in pixels.
 ///
 /// - `tl` - represents the top left point of the rectangle
-/// - `br` - represents the top left point of the rectangle
+/// - `br` - represents the bottom right point of the rectangle
 ///
 /// [`BBox`] implies that the uv coordinate system is used meaning
 /// y coordinate increases downwards.
+///
+/// Only the size of the box is used by the generation. Bitmaps are the box
+/// grown by the [`Offset`] on each side and the shape is positioned with
+/// `tl` at `(offset.x, offset.y)` of the bitmap.
+///
+/// Glyphs built by [`Glyph::build`] take the box from the pixel bounding
+/// box of the glyph placed with its origin (on the baseline) at
+/// `(offset.x, offset.y)`, so `tl - offset` is the top left corner relative
+/// to the glyph origin. Boxes of [`ShapeBuilder`](crate::ShapeBuilder)
+/// shapes go from `(0, 0)` to `(width, height)` in the builder
+/// coordinates.
 #[derive(Debug, Clone, Copy)]
 pub struct BBox {
     /// Top left point.
@@ -169,6 +744,11 @@ impl From<Rect<i32>> for BBox {
     }
 }
 
+/// Empty space in pixels added around the outline in the generated
+/// bitmap, where the distance field fades out.
+///
+/// `x` is added to both the left and the right side and `y` to both the
+/// top and the bottom, so the orientation of the y axis doesn't matter.
 #[derive(Debug, Clone, Copy)]
 pub struct Offset {
     pub x: f32,
@@ -187,6 +767,246 @@ impl Offset {
     }
 }
 
+/// Returns the font with a `kern` table of the `(left, right, value)`
+/// glyph id pairs since the bundled fonts only have `GPOS` kerning.
+#[cfg(test)]
+pub(crate) fn with_kern_table(
+    font: &[u8],
+    pairs: &[(u16, u16, i16)],
+) -> Vec<u8> {
+    let read = |at: usize| {
+        u32::from_be_bytes(font[at..at + 4].try_into().unwrap()) as usize
+    };
+
+    // Version 0 table with one horizontal format 0 subtable.
+    let mut kern = Vec::new();
+    let subtable_len = 14 + 6 * pairs.len();
+    for value in [0, 1, 0, subtable_len as u16, 1, pairs.len() as u16, 0, 0, 0]
+    {
+        kern.extend(value.to_be_bytes());
+    }
+    let mut pairs = pairs.to_vec();
+    pairs.sort();
+    for (left, right, value) in pairs {
+        kern.extend(left.to_be_bytes());
+        kern.extend(right.to_be_bytes());
+        kern.extend(value.to_be_bytes());
+    }
+
+    let table_count = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let mut tables = (0..table_count)
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let (offset, len) = (read(record + 8), read(record + 12));
+            (&font[record..record + 4], &font[offset..offset + len])
+        })
+        .collect::<Vec<_>>();
+    tables.push((b"kern", &kern));
+    tables.sort();
+
+    let mut data = font[..12].to_vec();
+    data[4..6].copy_from_slice(&(tables.len() as u16).to_be_bytes());
+    let mut body = Vec::new();
+    for (tag, table) in tables.iter() {
+        let offset = 12 + 16 * tables.len() + body.len();
+        data.extend_from_slice(tag);
+        // Checksums are not verified.
+        data.extend(0u32.to_be_bytes());
+        data.extend((offset as u32).to_be_bytes());
+        data.extend((table.len() as u32).to_be_bytes());
+        body.extend_from_slice(table);
+        body.resize((body.len() + 3) / 4 * 4, 0);
+    }
+    data.extend(body);
+    data
+}
+
+#[test]
+fn metrics_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let scale = Scale::uniform(32.0);
+
+    let glyph = font.glyph('A');
+    let h_metrics = glyph.h_metrics(scale);
+    assert!(h_metrics.advance_width > 0.0);
+    let outline = glyph.build(scale, Offset::uniform(2.0)).unwrap();
+    assert_eq!(outline.h_metrics().advance_width, h_metrics.advance_width);
+
+    let space = font.glyph(' ').h_metrics(scale);
+    assert!(space.advance_width > 0.0 && space.advance_width < 32.0);
+
+    // Roboto only has `GPOS` kerning.
+    assert_eq!(font.pair_kerning(scale, 'A', 'V'), 0.0);
+    assert_eq!(font.kerning_pairs(scale, "AVTo".chars()).count(), 0);
+
+    let (a, v) = (font.glyph('A').id(), font.glyph('V').id());
+    let kerned =
+        Font::from_vec(with_kern_table(data, &[(a, v, -400)])).unwrap();
+    let v_metrics = kerned.v_metrics_unscaled();
+    let expected = -400.0 * 32.0 / (v_metrics.ascent - v_metrics.descent);
+    assert_eq!(kerned.pair_kerning(scale, 'A', 'V'), expected);
+    assert_eq!(kerned.pair_kerning(scale, 'V', 'A'), 0.0);
+    let pairs = kerned
+        .kerning_pairs(scale, "AVTo".chars())
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, [('A', 'V', expected)]);
+
+    // Only pairs from the table are looked up, in the charset order.
+    let (t, o) = (font.glyph('T').id(), font.glyph('o').id());
+    let pairs = [(t, o, -100), (a, v, -400), (v, a, -300), (o, t, 50)];
+    let kerned = Font::from_vec(with_kern_table(data, &pairs)).unwrap();
+    let chars = ['o', 'V', 'A', 'T'];
+    let all_pairs = chars
+        .iter()
+        .flat_map(|&a| chars.iter().map(move |&b| (a, b)))
+        .map(|(a, b)| (a, b, kerned.pair_kerning(scale, a, b)))
+        .filter(|&(_, _, kerning)| kerning != 0.0)
+        .collect::<Vec<_>>();
+    let pairs = kerned
+        .kerning_pairs(scale, "oVATTVo".chars())
+        .collect::<Vec<_>>();
+    assert_eq!(pairs.len(), 4);
+    assert_eq!(pairs, all_pairs);
+}
+
+#[test]
+fn font_errors_test() {
+    let data = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+
+    assert_eq!(
+        Font::from_slice(&data[..100]).err(),
+        Some(Error::InvalidFontData)
+    );
+
+    let font = Font::from_slice(data).unwrap();
+    let scale = Scale::uniform(20.0);
+    let offset = Offset::uniform(2.0);
+
+    assert!(font.glyph('A').build(scale, offset).is_ok());
+
+    let space = font.glyph(' ').build(scale, offset).unwrap();
+    assert!(space.is_empty());
+    assert!(space.h_metrics().advance_width > 0.0);
+    let bitmap = space.generate_msdf(4);
+    assert_eq!((bitmap.width, bitmap.height), (0, 0));
+    assert!(bitmap.data.is_empty());
+    assert_eq!(
+        font.try_glyph('\u{10FFFF}').err(),
+        Some(Error::MissingGlyph('\u{10FFFF}'))
+    );
+}
+
+#[test]
+fn glyph_id_test() {
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+
+    let a = font.glyph('A').id();
+    assert_eq!(font.glyph(GlyphId(a)).id(), a);
+    let ids = font.glyph_ids().collect::<Vec<_>>();
+    assert_eq!(ids.len(), font.glyph_count());
+    assert_eq!(ids[0], GlyphId(0));
+
+    let mappings = font.char_mappings().collect::<Vec<_>>();
+    assert!(mappings.contains(&('A', GlyphId(a))));
+    assert!(mappings.iter().all(|&(_, id)| (id.0 as usize) < ids.len()));
+    assert!(!mappings.iter().any(|&(c, _)| c == '\u{10FFFF}'));
+    assert!(mappings.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    let chars = mappings.iter().map(|&(c, _)| c).collect::<Vec<_>>();
+    let mapped = ('\0'..='\u{FFFF}').filter(|&c| font.glyph(c).id() != 0);
+    assert!(mapped.eq(chars.iter().copied().filter(|&c| c <= '\u{FFFF}')));
+}
+
+#[test]
+fn collection_test() {
+    /// Packs the fonts into a collection, moving their tables.
+    fn collection(fonts: &[&[u8]]) -> Vec<u8> {
+        let read = |data: &[u8], at: usize| {
+            u32::from_be_bytes(data[at..at + 4].try_into().unwrap())
+        };
+        let mut data = b"ttcf\0\x01\0\0".to_vec();
+        data.extend((fonts.len() as u32).to_be_bytes());
+        let mut offset = data.len() + 4 * fonts.len();
+        let mut faces = Vec::new();
+        for font in fonts {
+            data.extend((offset as u32).to_be_bytes());
+            let mut face = font.to_vec();
+            let table_count = u16::from_be_bytes([face[4], face[5]]) as usize;
+            for record in (12..12 + 16 * table_count).step_by(16) {
+                let moved = read(&face, record + 8) + offset as u32;
+                face[record + 8..record + 12]
+                    .copy_from_slice(&moved.to_be_bytes());
+            }
+            offset += face.len();
+            faces.extend(face);
+        }
+        data.extend(faces);
+        data
+    }
+
+    let roboto = include_bytes!("../examples/fonts/Roboto-Regular.ttf");
+    let monserat = include_bytes!("../examples/fonts/monserat.ttf");
+    let data = collection(&[roboto, monserat]);
+
+    assert_eq!(Font::face_count(roboto), 1);
+    assert_eq!(Font::face_count(&data), 2);
+    let faces = Font::faces(&data).collect::<Vec<_>>();
+    let monserat_face = Font::faces(monserat).next().unwrap();
+    assert_eq!(
+        faces[1],
+        FaceInfo {
+            index: 1,
+            ..monserat_face
+        }
+    );
+    assert_eq!(faces[0].family_name.as_deref(), Some("Roboto"));
+    assert_eq!(faces[0].style_name.as_deref(), Some("Regular"));
+
+    let face = Font::from_collection(&data, 1).unwrap();
+    let expected = Font::from_slice(monserat).unwrap();
+    assert_eq!(face.glyph_count(), expected.glyph_count());
+    let owned = Font::from_collection_vec(data.clone(), 0).unwrap();
+    assert_eq!(
+        owned.glyph_count(),
+        Font::from_slice(roboto).unwrap().glyph_count()
+    );
+    assert_eq!(
+        Font::from_collection(&data, 2).err(),
+        Some(Error::MissingFace(2))
+    );
+}
+
+#[test]
+fn owned_font_test() {
+    fn assert_send_sync<T: Send + Sync + 'static>(_: &T) {}
+
+    let path =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/examples/fonts/monserat.ttf");
+    let font = Font::from_file(path).unwrap();
+    assert_send_sync(&font);
+
+    let data = std::fs::read(path).unwrap();
+    let borrowed = Font::from_slice(&data).unwrap();
+    let scale = Scale::uniform(24.0);
+    let outline = std::thread::spawn(move || {
+        font.glyph('g').build(scale, Offset::uniform(2.0)).unwrap()
+    })
+    .join()
+    .unwrap();
+    let expected = borrowed.glyph('g').build(scale, Offset::uniform(2.0));
+    assert_eq!(outline.generate_sdf(4), expected.unwrap().generate_sdf(4));
+
+    assert_eq!(
+        Font::from_vec(vec![0; 16]).err(),
+        Some(Error::InvalidFontData)
+    );
+    assert_eq!(
+        Font::from_file("missing.ttf").err(),
+        Some(Error::Io(std::io::ErrorKind::NotFound))
+    );
+}
+
 // TODO is needed?? /// Used for scaling glyphs and getting the desired output dimensions.
 // #[derive(Debug, Clone, Copy)]
 // pub struct Scale {
@@ -271,18 +1091,3 @@ impl Offset {
 //         }
 //     }
 // }
-
-//#[derive(Debug, Clone, Copy)]
-//pub struct GlyphId(pub u16);
-//
-//impl From<char> for GlyphId {
-//    fn from(c: char) -> Self {
-//        GlyphId(c as u16)
-//    }
-//}
-//
-//impl Into<owned_ttf_parser::GlyphId> for GlyphId {
-//    fn into(self) -> owned_ttf_parser::GlyphId {
-//        owned_ttf_parser::GlyphId(self.0)
-//    }
-//}
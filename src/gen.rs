@@ -1,83 +1,631 @@
 use crate::{
-    font::GlyphOutline, math::Distance, shape::Shape, vector::Vector2,
+    bvh::Bvh,
+    font::GlyphOutline,
+    math::{self, Distance},
+    range::{Range, RangeUnit},
+    shape::FillRule,
+    vector::Vector2,
 };
 
-pub struct Bitmap {
-    pub data: Vec<u8>,
+/// Type of the generated distance field.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldType {
+    /// Single-channel true signed distance field.
+    Sdf,
+    /// Single-channel pseudo signed distance field.
+    PseudoSdf,
+    /// Three-channel (RGB) multi-channel signed distance field.
+    #[default]
+    Msdf,
+    /// Four-channel (RGBA) multi-channel and true signed distance field.
+    Mtsdf,
+}
+
+impl FieldType {
+    /// Returns the number of channels per pixel.
+    #[inline]
+    pub fn channels(self) -> u32 {
+        match self {
+            FieldType::Sdf | FieldType::PseudoSdf => 1,
+            FieldType::Msdf => 3,
+            FieldType::Mtsdf => 4,
+        }
+    }
+}
+
+/// Image with interleaved channels.
+///
+/// Distance fields are stored as `u8` by default. A [`FloatBitmap`] holds
+/// raw signed distances which can be converted to `u8` or `u16` later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap<T = u8> {
+    pub data: Vec<T>,
     pub width: u32,
     pub height: u32,
+    /// Number of interleaved channels per pixel.
+    pub channels: u32,
 }
 
-impl Bitmap {
-    pub fn data(self) -> Vec<u8> {
+/// Bitmap of unclamped signed distances in pixels, positive inside of the
+/// shape.
+pub type FloatBitmap = Bitmap<f32>;
+
+impl<T> Bitmap<T> {
+    /// Creates a zero size bitmap.
+    pub fn empty(channels: u32) -> Self {
+        Self {
+            data: Vec::new(),
+            width: 0,
+            height: 0,
+            channels,
+        }
+    }
+
+    pub fn data(self) -> Vec<T> {
         self.data
     }
+
+    /// Reverses the order of rows.
+    pub fn flip_y(&mut self) {
+        let row_len = (self.width * self.channels) as usize;
+        if row_len == 0 {
+            return;
+        }
+        let mut rows = self.data.chunks_exact_mut(row_len);
+        while let (Some(top), Some(bottom)) = (rows.next(), rows.next_back()) {
+            top.swap_with_slice(bottom);
+        }
+    }
+}
+
+impl Bitmap<u16> {
+    /// Returns the data as little-endian bytes, ready for uploading into
+    /// 16-bit normalized textures.
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.data.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+}
+
+impl FloatBitmap {
+    /// Maps distances from the range to `[0, 255]`.
+    ///
+    /// The range has to be in pixels, convert ranges in ems with
+    /// [`Range::to_pixels`] first.
+    pub fn to_u8(&self, range: impl Into<Range>) -> Bitmap<u8> {
+        let range = range.into();
+        debug_assert_eq!(range.unit, RangeUnit::Pixels);
+        self.map(|distance| normalize(distance, &range))
+    }
+
+    /// Maps distances from the range to `[0, 65535]`.
+    ///
+    /// The range has to be in pixels, convert ranges in ems with
+    /// [`Range::to_pixels`] first.
+    pub fn to_u16(&self, range: impl Into<Range>) -> Bitmap<u16> {
+        let range = range.into();
+        debug_assert_eq!(range.unit, RangeUnit::Pixels);
+        self.map(|distance| normalize_u16(distance, &range))
+    }
+
+    /// Converts distances from pixels to em units.
+    ///
+    /// See [`Font::pixels_per_em`](crate::Font::pixels_per_em) for obtaining
+    /// `pixels_per_em`.
+    pub fn to_em(&self, pixels_per_em: f32) -> FloatBitmap {
+        self.map(|distance| distance / pixels_per_em)
+    }
+
+    fn map<U>(&self, f: impl Fn(f32) -> U) -> Bitmap<U> {
+        Bitmap {
+            data: self.data.iter().map(|&distance| f(distance)).collect(),
+            width: self.width,
+            height: self.height,
+            channels: self.channels,
+        }
+    }
+}
+
+/// Generates a 16-bit distance field of the provided type with the same
+/// range mapping as the 8-bit generators. Fits `R16_UNORM` and `RGBA16_UNORM` textures.
+pub fn gen_u16(
+    outline: &GlyphOutline,
+    field_type: FieldType,
+    range: Range,
+) -> Bitmap<u16> {
+    gen_float(outline, field_type).to_u16(outline.pixel_range(range))
+}
+
+/// Generates a distance field of the provided type holding raw signed
+/// distances in pixels.
+pub fn gen_float(outline: &GlyphOutline, field_type: FieldType) -> FloatBitmap {
+    match field_type {
+        FieldType::Sdf => gen_sdf_float(outline),
+        FieldType::PseudoSdf => gen_pseudo_sdf_float(outline),
+        FieldType::Msdf => gen_msdf_float(outline),
+        FieldType::Mtsdf => gen_mtsdf_float(outline),
+    }
 }
 
-pub fn gen_sdf(outline: &GlyphOutline, range: usize) -> Bitmap {
-    let shape = &outline.shape;
+pub fn gen_sdf(outline: &GlyphOutline, range: Range) -> Bitmap {
+    gen_sdf_float(outline).to_u8(outline.pixel_range(range))
+}
+
+/// Generates a single-channel field with the pseudo signed distance, the
+/// distance to the closest segment extended past its endpoints.
+pub fn gen_pseudo_sdf(outline: &GlyphOutline, range: Range) -> Bitmap {
+    gen_pseudo_sdf_float(outline).to_u8(outline.pixel_range(range))
+}
+
+/// Generates a multi-channel signed distance field where each of the
+/// red, green and blue channels holds the pseudo signed distance to the
+/// closest segment of the matching edge color.
+///
+/// The true distance is reconstructed by taking the median of all
+/// three channels, which preserves sharp corners.
+pub fn gen_msdf(outline: &GlyphOutline, range: Range) -> Bitmap {
+    gen_msdf_float(outline).to_u8(outline.pixel_range(range))
+}
+
+/// Generates a multi-channel and true signed distance field where the red,
+/// green and blue channels hold the same distances as [`gen_msdf`] and
+/// the alpha channel holds the true signed distance like in [`gen_sdf`].
+pub fn gen_mtsdf(outline: &GlyphOutline, range: Range) -> Bitmap {
+    gen_mtsdf_float(outline).to_u8(outline.pixel_range(range))
+}
+
+pub fn gen_sdf_float(outline: &GlyphOutline) -> FloatBitmap {
+    if outline.is_empty() {
+        return Bitmap::empty(1);
+    }
+    let bvh = Bvh::new(&outline.shape);
     let width = outline.width() + outline.offset.x as i32 * 2;
     let height = outline.height() + outline.offset.y as i32 * 2;
-    let mut data = Vec::new();
-    println!("width: {}, height: {}", width, height);
-    for y in 0..height {
-        for x in 0..width {
-            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
 
-            let signed_distance = shortest_distance(shape, pixel).real_signed();
+    gen_pixels(width as u32, height as u32, 1, |pixel, out| {
+        out[0] = bvh.shortest_distance(pixel).real_signed();
+    })
+}
+
+/// Float variant of [`gen_pseudo_sdf`].
+pub fn gen_pseudo_sdf_float(outline: &GlyphOutline) -> FloatBitmap {
+    if outline.is_empty() {
+        return Bitmap::empty(1);
+    }
+    let bvh = Bvh::new(&outline.shape);
+    let width = outline.width() + outline.offset.x as i32 * 2;
+    let height = outline.height() + outline.offset.y as i32 * 2;
 
-            let normalized = (signed_distance / range as f32) + 0.5;
+    gen_pixels(width as u32, height as u32, 1, |pixel, out| {
+        out[0] = bvh.shortest_distance(pixel).pseudo_signed();
+    })
+}
 
-            // When f32 is being converted to u8 it is automatically
-            // clamped in range [0, 255].
-            let sdf = (normalized * 255.0) as u8;
+/// Float variant of [`gen_msdf`].
+pub fn gen_msdf_float(outline: &GlyphOutline) -> FloatBitmap {
+    if outline.is_empty() {
+        return Bitmap::empty(3);
+    }
+    let bvh = Bvh::new(&outline.shape);
+    let width = outline.width() + outline.offset.x as i32 * 2;
+    let height = outline.height() + outline.offset.y as i32 * 2;
+
+    gen_pixels(width as u32, height as u32, 3, |pixel, out| {
+        let distance = shortest_multi_distance(&bvh, pixel);
+
+        out[0] = distance.r.pseudo_signed();
+        out[1] = distance.g.pseudo_signed();
+        out[2] = distance.b.pseudo_signed();
+    })
+}
+
+/// Float variant of [`gen_mtsdf`].
+pub fn gen_mtsdf_float(outline: &GlyphOutline) -> FloatBitmap {
+    if outline.is_empty() {
+        return Bitmap::empty(4);
+    }
+    let bvh = Bvh::new(&outline.shape);
+    let width = outline.width() + outline.offset.x as i32 * 2;
+    let height = outline.height() + outline.offset.y as i32 * 2;
+
+    gen_pixels(width as u32, height as u32, 4, |pixel, out| {
+        let distance = shortest_multi_distance(&bvh, pixel);
+
+        out[0] = distance.r.pseudo_signed();
+        out[1] = distance.g.pseudo_signed();
+        out[2] = distance.b.pseudo_signed();
+        out[3] = distance.shortest.real_signed();
+    })
+}
+
+/// Flips the distance of pixels whose sign disagrees with the scanline fill
+/// of the outline's shape.
+///
+/// Single-channel pixels are checked directly, multi-channel pixels by the
+/// median of the red, green and blue channel. All channels of a wrong pixel
+/// get mirrored around the value of the edge, which is the middle value
+/// for symmetric ranges. The bitmap has to be generated with the `range`.
+///
+/// Values clamped by an asymmetric range can't be mirrored exactly, use
+/// [`correct_sign_float`] before the conversion for exact results.
+pub fn correct_sign(
+    outline: &GlyphOutline,
+    bitmap: &mut Bitmap,
+    range: Range,
+    fill_rule: FillRule,
+) {
+    let edge = outline.pixel_range(range).normalize(0.0) * 255.0;
+    let is_inside = |pixel: &[u8]| {
+        let value = match pixel.len() {
+            1 => pixel[0] as f32,
+            _ => {
+                math::median(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32)
+            }
+        };
+        value > edge
+    };
+    correct_sign_with(outline, bitmap, fill_rule, is_inside, |channel| {
+        // Saturating casts clamp the value to `[0, 255]`.
+        *channel = (2.0 * edge - *channel as f32).round() as u8
+    });
+}
 
-            data.push(sdf);
+/// Float variant of [`correct_sign`]. Distances of wrong pixels get
+/// negated.
+pub fn correct_sign_float(
+    outline: &GlyphOutline,
+    bitmap: &mut FloatBitmap,
+    fill_rule: FillRule,
+) {
+    let is_inside = |pixel: &[f32]| {
+        let value = match pixel.len() {
+            1 => pixel[0],
+            _ => math::median(pixel[0], pixel[1], pixel[2]),
+        };
+        value > 0.0
+    };
+    correct_sign_with(outline, bitmap, fill_rule, is_inside, |channel| {
+        *channel = -*channel
+    });
+}
+
+fn correct_sign_with<T>(
+    outline: &GlyphOutline,
+    bitmap: &mut Bitmap<T>,
+    fill_rule: FillRule,
+    is_inside: impl Fn(&[T]) -> bool,
+    flip: impl Fn(&mut T),
+) {
+    let channels = bitmap.channels as usize;
+    let row_len = bitmap.width as usize * channels;
+    if row_len == 0 {
+        return;
+    }
+
+    for (y, row) in bitmap.data.chunks_exact_mut(row_len).enumerate() {
+        let crossings = outline.shape.scanline(y as f32 + 0.5);
+        for (x, pixel) in row.chunks_exact_mut(channels).enumerate() {
+            let x = x as f32 + 0.5;
+            let winding_number = crossings
+                .iter()
+                .filter(|(crossing_x, _)| *crossing_x > x)
+                .map(|(_, direction)| direction)
+                .sum();
+            let filled = fill_rule.is_filled(winding_number);
+
+            if is_inside(pixel) != filled {
+                pixel.iter_mut().for_each(&flip);
+            }
+        }
+    }
+}
+
+/// Creates a bitmap by calling `pixel_fn` with the center of each pixel
+/// and the pixel's channels to write into.
+///
+/// With the `parallel` feature rows are generated concurrently. Each pixel
+/// only depends on its own position so the output is the same either way.
+fn gen_pixels<F>(
+    width: u32,
+    height: u32,
+    channels: u32,
+    pixel_fn: F,
+) -> FloatBitmap
+where
+    F: Fn(Vector2<f32>, &mut [f32]) + Sync,
+{
+    let row_len = (width * channels) as usize;
+    let mut data = vec![0.0; row_len * height as usize];
+
+    let gen_row = |(y, row): (usize, &mut [f32])| {
+        for (x, out) in row.chunks_exact_mut(channels as usize).enumerate() {
+            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            pixel_fn(pixel, out);
+        }
+    };
+
+    if row_len > 0 {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            data.par_chunks_exact_mut(row_len)
+                .enumerate()
+                .for_each(gen_row);
         }
+        #[cfg(not(feature = "parallel"))]
+        data.chunks_exact_mut(row_len).enumerate().for_each(gen_row);
     }
 
     Bitmap {
         data,
-        width: width as u32,
-        height: height as u32,
+        width,
+        height,
+        channels,
     }
 }
 
-pub fn gen_pseudo_sdf(outline: &GlyphOutline, range: usize) -> Bitmap {
-    let shape = &outline.shape;
-    let width = outline.width();
-    let height = outline.height();
-    let mut data = Vec::new();
-    println!("width: {}, height: {}", width, height);
-    for y in 0..height as usize {
-        for x in 0..width as usize {
-            let pixel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+/// Maps the signed distance from the range to `[0, 255]`.
+#[inline]
+pub(crate) fn normalize(signed_distance: f32, range: &Range) -> u8 {
+    let normalized = range.normalize(signed_distance);
 
-            let signed_distance = shortest_distance(shape, pixel).real_signed();
+    // When f32 is being converted to u8 it is automatically
+    // clamped in range [0, 255].
+    (normalized * 255.0) as u8
+}
 
-            let normalized = (signed_distance / range as f32) + 0.5;
+/// Maps the signed distance from the range to `[0, 65535]`.
+#[inline]
+fn normalize_u16(signed_distance: f32, range: &Range) -> u16 {
+    let normalized = range.normalize(signed_distance);
 
-            // When f32 is being converted to u8 it is automatically
-            // clamped in range [0, 255].
-            let pseudo = (normalized * 255.0) as u8;
+    // Clamped the same way as in `normalize`.
+    (normalized * u16::MAX as f32) as u16
+}
 
-            data.push(pseudo);
+/// Closest distance for each of the three channels
+/// and the closest distance overall.
+struct MultiDistance {
+    r: Distance,
+    g: Distance,
+    b: Distance,
+    shortest: Distance,
+}
+
+/// Returns [`MultiDistance`] where each channel holds the [`Distance`]
+/// to the closest segment with the matching edge color.
+fn shortest_multi_distance(bvh: &Bvh, pixel: Vector2<f32>) -> MultiDistance {
+    let [mut r, mut g, mut b, shortest] = bvh.shortest_multi_distance(pixel);
+
+    // Channels without any segment fall back to the true distance.
+    for channel in [&mut r, &mut g, &mut b] {
+        if channel.real_dist == f32::MAX {
+            *channel = shortest;
         }
     }
 
-    Bitmap {
-        data,
-        width: width as u32,
-        height: height as u32,
+    MultiDistance { r, g, b, shortest }
+}
+
+#[test]
+fn msdf_median_test() {
+    use crate::{font::Offset, math::median, path::ShapeBuilder};
+
+    let mut builder = ShapeBuilder::new(20, 20, None, Offset::uniform(0.0));
+    builder.open_at(4.0, 4.0);
+    builder.line_to(16.0, 4.0);
+    builder.line_to(16.0, 16.0);
+    builder.line_to(4.0, 16.0);
+    builder.line_to(4.0, 4.0);
+    builder.close().unwrap();
+    let (shape, bbox) = builder.build().unwrap();
+    let outline = GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
+
+    let msdf = gen_msdf(&outline, 8.into());
+    let sdf = gen_sdf(&outline, 8.into());
+    assert_eq!(msdf.channels, 3);
+    assert_eq!(msdf.data.len(), sdf.data.len() * 3);
+
+    let median_at = |x: u32, y: u32| {
+        let i = ((y * msdf.width + x) * 3) as usize;
+        let [r, g, b] = [msdf.data[i], msdf.data[i + 1], msdf.data[i + 2]];
+        median(r as f32, g as f32, b as f32)
+    };
+    let sdf_at = |x: u32, y: u32| sdf.data[(y * sdf.width + x) as usize];
+
+    // Inside and outside must agree with the true signed distance field.
+    assert!(median_at(10, 10) > 127.0 && sdf_at(10, 10) > 127);
+    assert!(median_at(1, 1) < 127.0 && sdf_at(1, 1) < 127);
+    assert!(median_at(18, 10) < 127.0 && sdf_at(18, 10) < 127);
+}
+
+#[test]
+fn mtsdf_alpha_test() {
+    use crate::{font::Offset, path::ShapeBuilder};
+
+    let mut builder = ShapeBuilder::new(16, 16, None, Offset::uniform(0.0));
+    builder.open_at(2.0, 2.0);
+    builder.line_to(14.0, 2.0);
+    builder.quad_to(14.0, 14.0, 2.0, 14.0);
+    builder.line_to(2.0, 2.0);
+    builder.close().unwrap();
+    let (shape, bbox) = builder.build().unwrap();
+    let outline = GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
+
+    let mtsdf = gen_mtsdf(&outline, 6.into());
+    let msdf = gen_msdf(&outline, 6.into());
+    let sdf = gen_sdf(&outline, 6.into());
+    assert_eq!(mtsdf.channels, 4);
+
+    for (i, pixel) in mtsdf.data.chunks_exact(4).enumerate() {
+        assert_eq!(pixel[..3], msdf.data[i * 3..i * 3 + 3]);
+        assert_eq!(pixel[3], sdf.data[i]);
+    }
+}
+
+#[test]
+fn pseudo_sdf_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('k')
+        .build(Scale::uniform(32.0), Offset::uniform(3.0))
+        .unwrap();
+
+    let sdf = gen_sdf_float(&outline);
+    let pseudo = gen_pseudo_sdf_float(&outline);
+    assert_eq!((pseudo.width, pseudo.height), (sdf.width, sdf.height));
+
+    // Pseudo distances have the same sign and are never longer.
+    for (&p, &d) in pseudo.data.iter().zip(&sdf.data) {
+        assert_eq!(p.is_sign_negative(), d.is_sign_negative());
+        assert!(p.abs() <= d.abs() + 1e-3);
+    }
+    // Past the corners the extended segments are closer.
+    let shorter = |(p, d): (&f32, &f32)| d.abs() - p.abs() > 0.5;
+    assert!(pseudo.data.iter().zip(&sdf.data).any(shorter));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_gen_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('@')
+        .build(Scale::uniform(32.0), Offset::uniform(2.0))
+        .unwrap();
+
+    let single_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    for field_type in [FieldType::Sdf, FieldType::Msdf, FieldType::Mtsdf] {
+        let serial = single_thread.install(|| gen_float(&outline, field_type));
+        let parallel = gen_float(&outline, field_type);
+        assert_eq!(serial.data, parallel.data);
     }
 }
 
-/// Returns [`Distance`]
-fn shortest_distance(shape: &Shape, pixel: Vector2<f32>) -> Distance {
-    shape
-        .contours
-        .iter()
-        .map(|contour| contour.distance(pixel))
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .expect("Error: There are no distances??")
+#[test]
+fn sign_correction_test() {
+    use crate::{font::Offset, path::ShapeBuilder};
+
+    // A square inside of another square with the same winding.
+    let build = || {
+        let mut builder = ShapeBuilder::new(24, 24, None, Offset::uniform(0.0));
+        for (from, to) in [(2.0, 22.0), (8.0, 16.0)] {
+            builder.open_at(from, from);
+            builder.line_to(to, from);
+            builder.line_to(to, to);
+            builder.line_to(from, to);
+            builder.line_to(from, from);
+            builder.close().unwrap();
+        }
+        builder.build().unwrap()
+    };
+    let (shape, bbox) = build();
+    let mut outline =
+        GlyphOutline::from_shape(shape, bbox, Offset::uniform(0.0));
+    // Keep the inner square which the overlap correction removes.
+    outline.shape = build().0;
+    let center = (12 * 24 + 12) * 3;
+
+    let mut msdf = gen_msdf(&outline, 8.into());
+    assert!(msdf.data[center] > 127);
+    correct_sign(&outline, &mut msdf, 8.into(), FillRule::NonZero);
+    assert!(msdf.data[center] > 127);
+
+    // The inner square is a hole by the even-odd rule.
+    let mut even_odd = gen_msdf(&outline, 8.into());
+    correct_sign(&outline, &mut even_odd, 8.into(), FillRule::EvenOdd);
+    assert!(even_odd.data[center] < 128);
+    assert_eq!(even_odd.data[..center / 2], msdf.data[..center / 2]);
+
+    // The edge of an asymmetric range is below the middle value.
+    let range = Range::asymmetric(1.0, 7.0, RangeUnit::Pixels);
+    let edge = range.normalize(0.0) * 255.0;
+    let mut asymmetric = gen_msdf(&outline, range);
+    correct_sign(&outline, &mut asymmetric, range, FillRule::EvenOdd);
+    let mut float = gen_msdf_float(&outline);
+    correct_sign_float(&outline, &mut float, FillRule::EvenOdd);
+    let expected = float.to_u8(range);
+    assert!((asymmetric.data[center] as f32) < edge);
+    for (pixel, expected) in asymmetric
+        .data
+        .chunks_exact(3)
+        .zip(expected.data.chunks_exact(3))
+    {
+        let inside = |p: &[u8]| {
+            math::median(p[0] as f32, p[1] as f32, p[2] as f32) > edge
+        };
+        assert_eq!(inside(pixel), inside(expected));
+    }
+}
+
+#[test]
+fn float_bitmap_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('g')
+        .build(Scale::uniform(24.0), Offset::uniform(2.0))
+        .unwrap();
+
+    for field_type in [FieldType::Sdf, FieldType::Msdf, FieldType::Mtsdf] {
+        let float = gen_float(&outline, field_type);
+        assert_eq!(float.channels, field_type.channels());
+    }
+    let range = 4.into();
+    assert_eq!(gen_sdf_float(&outline).to_u8(4.0), gen_sdf(&outline, range));
+    assert_eq!(
+        gen_msdf_float(&outline).to_u8(4.0),
+        gen_msdf(&outline, range)
+    );
+    assert_eq!(
+        gen_mtsdf_float(&outline).to_u8(4.0),
+        gen_mtsdf(&outline, range)
+    );
+
+    // Distances are not clamped to the range.
+    let sdf = gen_sdf_float(&outline);
+    assert!(sdf.data.iter().any(|&distance| distance < -4.0));
+    let wide = sdf.to_u16(64.0);
+    assert!(wide.data.iter().all(|&v| v > 0 && v < u16::MAX));
+
+    let ems = sdf.to_em(font.pixels_per_em(Scale::uniform(24.0)));
+    assert!(ems.data.iter().all(|&distance| distance.abs() < 1.0));
+}
+
+#[test]
+fn u16_output_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('e')
+        .build(Scale::uniform(24.0), Offset::uniform(2.0))
+        .unwrap();
+
+    for field_type in [FieldType::Sdf, FieldType::Msdf, FieldType::Mtsdf] {
+        let narrow = gen_float(&outline, field_type).to_u8(4.0);
+        let wide = gen_u16(&outline, field_type, 4.into());
+        assert_eq!(wide.channels, field_type.channels());
+        assert_eq!(wide.to_le_bytes().len(), wide.data.len() * 2);
+        // The high byte matches the 8-bit output up to rounding.
+        for (&n, &w) in narrow.data.iter().zip(&wide.data) {
+            assert!(n.abs_diff((w / 257) as u8) <= 1);
+        }
+    }
 }
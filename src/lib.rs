@@ -1,13 +1,33 @@
+mod atlas;
+mod bvh;
+mod coloring;
+mod config;
+mod correction;
+mod encode;
+mod error;
+mod export;
 mod font;
 mod gen;
 mod math;
 mod overlaps;
 mod path;
+mod range;
 mod shape;
 mod vector;
 
+pub use atlas::{
+    Atlas, AtlasGlyph, AtlasRect, AtlasSettings, GlyphPlacement, PlaneBounds,
+    UvRect,
+};
+pub use coloring::{ColoringStrategy, EdgeColor, DEFAULT_ANGLE_THRESHOLD};
+pub use config::{GeneratorConfig, OutputBitmap, OutputFormat, YAxis};
+pub use correction::{ErrorCorrection, ErrorCorrectionMode};
+pub use error::{Error, Result};
 pub use font::*;
+pub use gen::{Bitmap, FieldType, FloatBitmap};
 pub use path::ShapeBuilder;
+pub use range::{Range, RangeUnit};
+pub use shape::FillRule;
 pub use vector::Vector2;
 
-pub use rusttype::{Scale, VMetrics};
+pub use rusttype::{GlyphId, HMetrics, IntoGlyphId, Scale, VMetrics};
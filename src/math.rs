@@ -1,6 +1,6 @@
 use std::f32::consts::PI;
 
-use crate::shape::{Curve, Line, Quad};
+use crate::shape::{Curve, Line, Quad, Segment};
 use crate::vector::Vector2;
 
 // TODO is this needed?
@@ -156,7 +156,7 @@ pub fn quad_signed_distance(quad: &Quad, point: Vector2<f32>) -> Distance {
     let real_dist = smallest_dist2.sqrt();
 
     // Invert the vector to get distance from bezier line to "p". TODO explain
-    let dir = 2.0 * v2 * real_pos + 2.0 * v1;
+    let dir = quadratic_derivative_fn(p0, p1, p2, real_pos);
     let p_bezier = p - closest_bezier;
     let ortho: f32 = if p_bezier.is_zero() || dir.is_zero() {
         0.0
@@ -174,12 +174,95 @@ pub fn quad_signed_distance(quad: &Quad, point: Vector2<f32>) -> Distance {
     }
 }
 
-#[allow(unused)]
+/// Number of starting points for the Newton's method.
+const CUBIC_SEARCH_STARTS: usize = 4;
+/// Number of Newton's method iterations for each starting point.
+const CUBIC_SEARCH_STEPS: usize = 4;
+
+/// Unlike lines and quadratic curves, the closest point on a cubic curve
+/// requires solving a quintic equation. Instead, the curve is divided into
+/// [`CUBIC_SEARCH_STARTS`] parts and from each the closest point is refined
+/// with a few steps of Newton's method.
+///
+/// If the closest point is one of the end points, the extended distance is
+/// measured to the tangent line at that end point.
 pub fn curve_signed_distance(curve: &Curve, point: Vector2<f32>) -> Distance {
-    unimplemented!()
+    let p0 = curve.from;
+    let p1 = curve.ctrl1;
+    let p2 = curve.ctrl2;
+    let p3 = curve.to;
+    let p = point;
+
+    // Start with end points.
+    let mut real_pos = 0.0;
+    let mut smallest_dist2 = (p0 - p).magnitude2();
+    let end_dist2 = (p3 - p).magnitude2();
+    if end_dist2 < smallest_dist2 {
+        real_pos = 1.0;
+        smallest_dist2 = end_dist2;
+    }
+
+    // Minimize the squared distance f(t) = |B(t) - p|^2 where
+    // f'(t) / 2 = (B(t) - p) · B'(t) and
+    // f''(t) / 2 = B'(t) · B'(t) + (B(t) - p) · B''(t).
+    for i in 0..=CUBIC_SEARCH_STARTS {
+        let mut t = i as f32 / CUBIC_SEARCH_STARTS as f32;
+        for _ in 0..CUBIC_SEARCH_STEPS {
+            let bezier_p = cubic_fn(p0, p1, p2, p3, t) - p;
+            let d1 = cubic_derivative_fn(p0, p1, p2, p3, t);
+            let d2 = cubic_second_derivative_fn(p0, p1, p2, p3, t);
+            let denominator = d1.dot(d1) + bezier_p.dot(d2);
+            if denominator == 0.0 {
+                break;
+            }
+            t -= bezier_p.dot(d1) / denominator;
+            if !(0.0..=1.0).contains(&t) {
+                break;
+            }
+
+            let dist2 = (cubic_fn(p0, p1, p2, p3, t) - p).magnitude2();
+            if dist2 < smallest_dist2 {
+                real_pos = t;
+                smallest_dist2 = dist2;
+            }
+        }
+    }
+
+    let closest_bezier = cubic_fn(p0, p1, p2, p3, real_pos);
+    let real_dist = smallest_dist2.sqrt();
+    let dir = curve.direction(real_pos);
+    let p_bezier = p - closest_bezier;
+
+    // Extend the curve with a tangent line at the closest end point.
+    let extended_dist = if (real_pos == 0.0 && p_bezier.dot(dir) < 0.0)
+        || (real_pos == 1.0 && p_bezier.dot(dir) > 0.0)
+    {
+        if dir.is_zero() {
+            real_dist
+        } else {
+            dir.normalize().cross(p_bezier).abs()
+        }
+    } else {
+        real_dist
+    };
+
+    let ortho: f32 = if p_bezier.is_zero() || dir.is_zero() {
+        0.0
+    } else {
+        dir.normalize().cross(p_bezier.normalize())
+    };
+    let sign = ortho.signum();
+    let orthogonality = ortho.abs();
+
+    Distance {
+        extended_dist,
+        real_dist,
+        orthogonality,
+        sign,
+    }
 }
 
-fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+pub fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
     let discriminant = b * b - 4.0 * a * c;
 
     if a == 0.0 {
@@ -239,9 +322,14 @@ fn cubic_roots(a: f32, b: f32, c: f32, d: f32) -> [Option<f32>; 3] {
         // root2 = (2 * sqrt(-q)) * cos((theta + 2*pi)/3) - (third * b);
         // root3 = (2 * sqrt(-q)) * cos((theta + 4*pi)/3) - (third * b);
         // root = m * cos((theta + ...)/3) - n;
+        if q == 0.0 {
+            // r == 0.0 as well so there is a triple root.
+            return [Some(-b), None, None];
+        }
         let q_sqrt = q.sqrt();
         let two_pi = 2.0 * PI;
-        let theta = (r / q_sqrt.powi(3)).acos();
+        // Clamp to avoid NaN when rounding errors push the value out of range.
+        let theta = (r / q_sqrt.powi(3)).clamp(-1.0, 1.0).acos();
         let m = -2.0 * q_sqrt;
         let x1 = m * (theta * third).cos() - b;
         let x2 = m * ((theta + two_pi) * third).cos() - b;
@@ -251,9 +339,35 @@ fn cubic_roots(a: f32, b: f32, c: f32, d: f32) -> [Option<f32>; 3] {
     }
 }
 
-/// Takes four points as input where **p0-p1** create first line and **p2-p3** create
-/// second line. If they intersect the function returns `true` and if they don't
-/// `false` is returned.
+/// Returns the middle value of the three provided values.
+///
+/// Used for reconstructing the distance from the multi-channel
+/// distance field.
+#[inline]
+pub fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+/// Maximum size of subdivided curve pieces which are treated as lines
+/// when searching for curve-curve intersections.
+const INTERSECTION_TOLERANCE: f32 = 1e-4;
+/// Maximum number of subdivisions when searching for curve-curve
+/// intersections.
+const INTERSECTION_MAX_DEPTH: u32 = 48;
+
+/// Point where two segments intersect.
+///
+/// - `t1` - function parameter of the first segment at the intersection
+/// - `t2` - function parameter of the second segment at the intersection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub point: Vector2<f32>,
+    pub t1: f32,
+    pub t2: f32,
+}
+
+/// Takes two lines as input and returns the [`Intersection`] if they
+/// intersect.
 ///
 /// Even tho lines are touching when they are coincident or are on the same line touching,
 /// this function will not record it as an intersection.
@@ -263,7 +377,7 @@ fn cubic_roots(a: f32, b: f32, c: f32, d: f32) -> [Option<f32>; 3] {
 pub fn line_line_intersection(
     line1: &Line,
     line2: &Line,
-) -> Option<Vector2<f32>> {
+) -> Option<Intersection> {
     let p0 = line1.from;
     let p1 = line1.to;
     let p2 = line2.from;
@@ -283,10 +397,6 @@ pub fn line_line_intersection(
 
     // Even tho lines are touching when they are coincident or are on the
     // same line, this function will not record it as an intersection.
-    //if denominator == 0.0 && numerator1 == 0.0 && numerator2 == 0.0 {
-    //    // println!("COINCIDENT");
-    //    return None;
-    //}
     if denominator != 0.0 {
         // Variable of the first line function
         let t1 = numerator1 / denominator;
@@ -294,89 +404,196 @@ pub fn line_line_intersection(
         // Variable of the second line function
         let t2 = numerator2 / denominator;
 
-        // println!("t1: {}, t2: {}", t1, t2);
-
         if (0.0..=1.0).contains(&t1) && (0.0..=1.0).contains(&t2) {
-            //assert_eq!(line_fn(p0, p1, t1), line_fn(p2, p3, t2));
-            return Some(line_fn(p0, p1, t1));
+            return Some(Intersection {
+                point: line_fn(p0, p1, t1),
+                t1,
+                t2,
+            });
         }
     }
 
     None
 }
 
-// TODO exclain and improve and simplify
+/// Returns intersections of the quadratic curve (`t1`) and the line (`t2`).
+///
 /// Sources:
 /// https://www.particleincell.com/2013/cubic-line-intersection/
 /// https://stackoverflow.com/questions/50129580/program-to-find-line-segment-and-bezier-curve-intersection
 pub fn quad_line_intersection(
     quad: &Quad,
     line: &Line,
-) -> [Option<Vector2<f32>>; 2] {
+) -> [Option<Intersection>; 2] {
     let q0 = quad.from;
     let q1 = quad.ctrl;
     let q2 = quad.to;
     let l0 = line.from;
     let l1 = line.to;
+    let dir = l1 - l0;
 
+    // Points of the curve on the line have zero cross product with the
+    // line direction, which gives a quadratic equation for `t`.
     let mut intersections = [None; 2];
-    let a = (q0 + q2 - 2.0 * q1).cross(l1 - l0);
-    let b = 2.0 * (q1 - q0).cross(l1 - l0);
-    let c = (q0 - l0).cross(l1 - l0);
+    let a = (q0 + q2 - 2.0 * q1).cross(dir);
+    let b = 2.0 * (q1 - q0).cross(dir);
+    let c = (q0 - l0).cross(dir);
 
     let quad_roots = quadratic_roots(a, b, c);
 
     for (i, root) in quad_roots.iter().flatten().enumerate() {
-        if *root >= 0.0 && *root <= 1.0 {
-            let quad_p = quadratic_fn(q0, q1, q2, *root);
-            // TODO find out what "line_p" represents
-            let line_p = (quad_p - l0) / (l1 - l0);
-            if (line_p.x >= 0.0 && line_p.x <= 1.0)
-                && (line_p.y >= 0.0 && line_p.y <= 1.0)
-            {
-                // assert_eq!(line_fn(l0, l1, line_p.x), quad_p);
-                intersections[i] = Some(line_fn(l0, l1, line_p.x));
+        if (0.0..=1.0).contains(root) {
+            let point = quadratic_fn(q0, q1, q2, *root);
+            // Position of the point along the line.
+            let line_t = (point - l0).dot(dir) / dir.dot(dir);
+            if (0.0..=1.0).contains(&line_t) {
+                intersections[i] = Some(Intersection {
+                    point,
+                    t1: *root,
+                    t2: line_t,
+                });
             }
-            //println!("line: {:?}", line_p);
         }
     }
 
-    //println!("roots: {:?}", quad_roots);
-
     intersections
 }
 
-#[allow(unused)]
+/// Returns intersections of two quadratic curves.
 pub fn quad_quad_intersection(
     quad1: &Quad,
     quad2: &Quad,
-) -> [Option<Vector2<f32>>; 4] {
-    // TODO this
-    [None, None, None, None]
+) -> [Option<Intersection>; 4] {
+    subdivision_intersections(Segment::Quad(*quad1), Segment::Quad(*quad2))
 }
 
-#[allow(unused)]
+/// Returns intersections of the cubic curve (`t1`) and the line (`t2`).
+///
+/// Same as [`quad_line_intersection`] but with a cubic equation.
 pub fn curve_line_intersection(
     curve: &Curve,
     line: &Line,
-) -> [Option<Vector2<f32>>; 3] {
-    todo!()
+) -> [Option<Intersection>; 3] {
+    let p0 = curve.from;
+    let p1 = curve.ctrl1;
+    let p2 = curve.ctrl2;
+    let p3 = curve.to;
+    let l0 = line.from;
+    let dir = line.to - l0;
+
+    let mut intersections = [None; 3];
+    let a = (3.0 * (p1 - p2) + p3 - p0).cross(dir);
+    let b = (3.0 * (p0 - 2.0 * p1 + p2)).cross(dir);
+    let c = (3.0 * (p1 - p0)).cross(dir);
+    let d = (p0 - l0).cross(dir);
+
+    let cubic_roots = cubic_roots(a, b, c, d);
+
+    for (i, root) in cubic_roots.iter().flatten().enumerate() {
+        if (0.0..=1.0).contains(root) {
+            let point = cubic_fn(p0, p1, p2, p3, *root);
+            let line_t = (point - l0).dot(dir) / dir.dot(dir);
+            if (0.0..=1.0).contains(&line_t) {
+                intersections[i] = Some(Intersection {
+                    point,
+                    t1: *root,
+                    t2: line_t,
+                });
+            }
+        }
+    }
+
+    intersections
 }
 
-#[allow(unused)]
+/// Returns intersections of the cubic curve (`t1`) and the quadratic
+/// curve (`t2`).
 pub fn curve_quad_intersection(
     curve: &Curve,
     quad: &Quad,
-) -> [Option<Vector2<f32>>; 6] {
-    todo!()
+) -> [Option<Intersection>; 6] {
+    subdivision_intersections(Segment::Curve(*curve), Segment::Quad(*quad))
 }
 
-#[allow(unused)]
+/// Returns intersections of two cubic curves.
 pub fn curve_curve_intersection(
     curve1: &Curve,
     curve2: &Curve,
-) -> [Option<Vector2<f32>>; 9] {
-    todo!()
+) -> [Option<Intersection>; 9] {
+    subdivision_intersections(Segment::Curve(*curve1), Segment::Curve(*curve2))
+}
+
+/// Finds intersections of two segments by splitting them in halves until
+/// the pieces are small enough to be treated as lines. Pieces whose
+/// bounding boxes don't overlap are discarded.
+///
+/// Returns at most `N` intersections.
+fn subdivision_intersections<const N: usize>(
+    seg1: Segment,
+    seg2: Segment,
+) -> [Option<Intersection>; N] {
+    let mut found = Vec::new();
+    subdivide(seg1, (0.0, 1.0), seg2, (0.0, 1.0), 0, &mut found);
+
+    let mut intersections = [None; N];
+    for (slot, intersection) in intersections.iter_mut().zip(found) {
+        *slot = Some(intersection);
+    }
+    intersections
+}
+
+fn subdivide(
+    seg1: Segment,
+    range1: (f32, f32),
+    seg2: Segment,
+    range2: (f32, f32),
+    depth: u32,
+    found: &mut Vec<Intersection>,
+) {
+    let (min1, max1) = seg1.bounds();
+    let (min2, max2) = seg2.bounds();
+    if min1.x > max2.x || min2.x > max1.x || min1.y > max2.y || min2.y > max1.y
+    {
+        return;
+    }
+
+    let size1 = (max1.x - min1.x).max(max1.y - min1.y);
+    let size2 = (max2.x - min2.x).max(max2.y - min2.y);
+    let is_small =
+        size1 <= INTERSECTION_TOLERANCE && size2 <= INTERSECTION_TOLERANCE;
+    if is_small || depth >= INTERSECTION_MAX_DEPTH {
+        let chord1 = Line::new(seg1.point(0.0), seg1.point(1.0));
+        let chord2 = Line::new(seg2.point(0.0), seg2.point(1.0));
+        if let Some(inter) = line_line_intersection(&chord1, &chord2) {
+            let t1 = range1.0 + inter.t1 * (range1.1 - range1.0);
+            let t2 = range2.0 + inter.t2 * (range2.1 - range2.0);
+            // Neighboring pieces can both contain the same intersection.
+            let is_duplicate = found.iter().any(|other| {
+                (other.t1 - t1).abs() < 1e-3 && (other.t2 - t2).abs() < 1e-3
+            });
+            if !is_duplicate {
+                found.push(Intersection {
+                    point: inter.point,
+                    t1,
+                    t2,
+                });
+            }
+        }
+        return;
+    }
+
+    // Split the bigger one.
+    if size1 >= size2 {
+        let (first, second) = seg1.split(0.5);
+        let mid = 0.5 * (range1.0 + range1.1);
+        subdivide(first, (range1.0, mid), seg2, range2, depth + 1, found);
+        subdivide(second, (mid, range1.1), seg2, range2, depth + 1, found);
+    } else {
+        let (first, second) = seg2.split(0.5);
+        let mid = 0.5 * (range2.0 + range2.1);
+        subdivide(seg1, range1, first, (range2.0, mid), depth + 1, found);
+        subdivide(seg1, range1, second, (mid, range2.1), depth + 1, found);
+    }
 }
 
 /// A line function.
@@ -385,7 +602,6 @@ pub fn curve_curve_intersection(
 /// - `t` - function parameter
 // TODO maybe convert to macro!
 #[inline]
-#[allow(unused)]
 pub fn line_fn(p0: Vector2<f32>, p1: Vector2<f32>, t: f32) -> Vector2<f32> {
     p0 + t * (p1 - p0)
 }
@@ -397,7 +613,6 @@ pub fn line_fn(p0: Vector2<f32>, p1: Vector2<f32>, t: f32) -> Vector2<f32> {
 /// - `t` - function parameter
 // TODO maybe convert to macro!
 #[inline]
-#[allow(unused)]
 pub fn quadratic_fn(
     p0: Vector2<f32>,
     p1: Vector2<f32>,
@@ -407,6 +622,184 @@ pub fn quadratic_fn(
     p0 + 2.0 * t * (p1 - p0) + t * t * (p2 - 2.0 * p1 + p0)
 }
 
+/// Derivative of the quadratic function.
+/// - `p0` - curve starting point
+/// - `p1` - curve control point
+/// - `p2` - curve ending point
+/// - `t` - function parameter
+#[inline]
+pub fn quadratic_derivative_fn(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    t: f32,
+) -> Vector2<f32> {
+    2.0 * (p1 - p0) + 2.0 * t * (p2 - 2.0 * p1 + p0)
+}
+
+/// A cubic function.
+/// - `p0` - curve starting point
+/// - `p1` - curve first control point
+/// - `p2` - curve second control point
+/// - `p3` - curve ending point
+/// - `t` - function parameter
+#[inline]
+pub fn cubic_fn(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    p3: Vector2<f32>,
+    t: f32,
+) -> Vector2<f32> {
+    let s = 1.0 - t;
+    s * s * s * p0
+        + 3.0 * s * s * t * p1
+        + 3.0 * s * t * t * p2
+        + t * t * t * p3
+}
+
+/// Derivative of the cubic function.
+/// - `p0` - curve starting point
+/// - `p1` - curve first control point
+/// - `p2` - curve second control point
+/// - `p3` - curve ending point
+/// - `t` - function parameter
+#[inline]
+pub fn cubic_derivative_fn(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    p3: Vector2<f32>,
+    t: f32,
+) -> Vector2<f32> {
+    let s = 1.0 - t;
+    3.0 * s * s * (p1 - p0) + 6.0 * s * t * (p2 - p1) + 3.0 * t * t * (p3 - p2)
+}
+
+/// Second derivative of the cubic function.
+/// - `p0` - curve starting point
+/// - `p1` - curve first control point
+/// - `p2` - curve second control point
+/// - `p3` - curve ending point
+/// - `t` - function parameter
+#[inline]
+pub fn cubic_second_derivative_fn(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    p3: Vector2<f32>,
+    t: f32,
+) -> Vector2<f32> {
+    6.0 * (1.0 - t) * (p2 - 2.0 * p1 + p0) + 6.0 * t * (p3 - 2.0 * p2 + p1)
+}
+
+#[test]
+fn curve_distance_test() {
+    // Quadratic curve elevated to a cubic one describes the same curve.
+    let quad = Quad::new(
+        Vector2::new(1.0, 1.0),
+        Vector2::new(4.0, 12.0),
+        Vector2::new(11.0, 3.0),
+    );
+    let curve = Curve::new(
+        quad.from,
+        quad.from + (2.0 / 3.0) * (quad.ctrl - quad.from),
+        quad.to + (2.0 / 3.0) * (quad.ctrl - quad.to),
+        quad.to,
+    );
+
+    for y in 0..12 {
+        for x in 0..12 {
+            let point = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let expected = quad_signed_distance(&quad, point);
+            let result = curve_signed_distance(&curve, point);
+
+            assert!((expected.real_dist - result.real_dist).abs() < 0.01);
+            assert_eq!(expected.sign, result.sign);
+        }
+    }
+}
+
+#[test]
+fn curve_extended_distance_test() {
+    // Starts going right and ends going down.
+    let curve = Curve::new(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(3.0, 0.0),
+        Vector2::new(10.0, 7.0),
+        Vector2::new(10.0, 10.0),
+    );
+
+    // Behind the start the distance is measured to the tangent line.
+    let behind = curve_signed_distance(&curve, Vector2::new(-5.0, 2.0));
+    assert!((behind.real_dist - 29f32.sqrt()).abs() < 1e-4);
+    assert!((behind.extended_dist - 2.0).abs() < 1e-4);
+    assert!((behind.orthogonality - 2.0 / 29f32.sqrt()).abs() < 1e-4);
+
+    // Past the end as well.
+    let past = curve_signed_distance(&curve, Vector2::new(7.0, 14.0));
+    assert!((past.real_dist - 5.0).abs() < 1e-4);
+    assert!((past.extended_dist - 3.0).abs() < 1e-4);
+    assert!((past.orthogonality - 0.6).abs() < 1e-4);
+    // Both points are on the same side of the curve.
+    assert_eq!(behind.sign, past.sign);
+
+    // Next to the curve the closest point is orthogonal and nothing is
+    // extended.
+    let beside = curve_signed_distance(&curve, Vector2::new(8.0, 2.0));
+    assert_eq!(beside.extended_dist, beside.real_dist);
+    assert!((beside.orthogonality - 1.0).abs() < 1e-3);
+    assert_eq!(beside.sign, -behind.sign);
+}
+
+#[test]
+fn curve_brute_force_distance_test() {
+    let curves = [
+        // S-curve with an inflection point.
+        Curve::new(
+            Vector2::new(1.0, 1.0),
+            Vector2::new(14.0, 1.0),
+            Vector2::new(-2.0, 11.0),
+            Vector2::new(11.0, 11.0),
+        ),
+        // Loop.
+        Curve::new(
+            Vector2::new(1.0, 10.0),
+            Vector2::new(16.0, 0.0),
+            Vector2::new(-4.0, 0.0),
+            Vector2::new(11.0, 10.0),
+        ),
+        // Cusp.
+        Curve::new(
+            Vector2::new(1.0, 10.0),
+            Vector2::new(11.0, 1.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(11.0, 10.0),
+        ),
+    ];
+
+    for curve in curves {
+        let samples = (0..=10000)
+            .map(|i| {
+                let t = i as f32 / 10000.0;
+                cubic_fn(curve.from, curve.ctrl1, curve.ctrl2, curve.to, t)
+            })
+            .collect::<Vec<_>>();
+        for y in 0..12 {
+            for x in 0..12 {
+                let point = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let expected = samples
+                    .iter()
+                    .map(|&sample| (sample - point).magnitude())
+                    .fold(f32::INFINITY, f32::min);
+                let result = curve_signed_distance(&curve, point);
+
+                assert!((expected - result.real_dist).abs() < 0.01);
+            }
+        }
+    }
+}
+
 #[test]
 fn line_quad_intersection_test() {
     // TODO maybe add more tests
@@ -570,7 +963,7 @@ fn cubic_root_test() {
     assert!(discriminant > 0.0);
 }
 
-#[allow(unused)]
+#[cfg(test)]
 fn test_find_cubic_roots(
     _a: f32,
     _b: f32,
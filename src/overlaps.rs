@@ -1,127 +1,379 @@
-/*use crate::{
-    shape::{Curve, Line, Quad, Segment},
+use crate::{
+    math::{self, Intersection},
+    shape::{Contour, Curve, FillRule, Line, Quad, Segment, Shape, Winding},
     vector::Vector2,
 };
 
+/// Intersections closer than this to the segment ends are ignored since
+/// the segment is already split there.
+const END_TOLERANCE: f32 = 1e-4;
+/// Distance from the segment at which the fill is checked on both sides.
+const SIDE_OFFSET: f32 = 1e-3;
+/// Maximum distance between the end of a segment and the start of the
+/// next one when connecting segments back into contours.
+const JOIN_TOLERANCE: f32 = 1e-2;
+/// Parameters at which the fill is checked, in order. Tangents can be zero
+/// at a single point, like at a cusp of a cubic curve.
+const SIDE_PARAMS: [f32; 3] = [0.5, 0.25, 0.75];
+
+impl Shape {
+    /// Resolves overlapping contours (and self-intersecting ones) so that
+    /// the outline of the shape matches the area filled by the fill rule.
+    ///
+    /// Segments are split at intersection points and the parts which are
+    /// not on the border of the filled area, like parts inside of another
+    /// contour, are removed. Remaining parts are connected back into
+    /// contours. Parts with the filled area on the wrong side are reversed.
+    ///
+    /// Shapes which don't need the correction are left untouched, as well
+    /// as shapes whose parts can't be connected back into closed contours.
+    /// Returns `true` if the shape changed.
+    pub fn correct_overlaps(&mut self, fill_rule: FillRule) -> bool {
+        let segments = self
+            .contours
+            .iter()
+            .flat_map(|contour| contour.iter().copied())
+            .collect::<Vec<_>>();
+
+        // Split parameters and intersection points of each segment.
+        let mut splits: Vec<Vec<(f32, Vector2<f32>)>> =
+            vec![Vec::new(); segments.len()];
+        for i in 0..segments.len() {
+            let (min1, max1) = segments[i].bounds();
+            for j in i + 1..segments.len() {
+                let (min2, max2) = segments[j].bounds();
+                if min1.x > max2.x
+                    || min2.x > max1.x
+                    || min1.y > max2.y
+                    || min2.y > max1.y
+                {
+                    continue;
+                }
+                for inter in segments[i].intersections(&segments[j]) {
+                    if is_inner(inter.t1) {
+                        splits[i].push((inter.t1, inter.point));
+                    }
+                    if is_inner(inter.t2) {
+                        splits[j].push((inter.t2, inter.point));
+                    }
+                }
+            }
+        }
+
+        let mut changed = false;
+        let mut parts = Vec::new();
+        for (segment, mut splits) in segments.into_iter().zip(splits) {
+            splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+            splits.dedup_by(|a, b| a.0 - b.0 < END_TOLERANCE);
+            changed |= !splits.is_empty();
+
+            for part in split_at(segment, &splits) {
+                match self.border_side(&part, fill_rule) {
+                    Some(true) => parts.push(part),
+                    Some(false) => {
+                        changed = true;
+                        parts.push(part.reversed());
+                    }
+                    None => changed = true,
+                }
+            }
+        }
+
+        if !changed {
+            return false;
+        }
+        match connect(parts) {
+            Some(contours) => {
+                self.contours = contours;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks on which side of the segment the filled area is.
+    ///
+    /// Returns `Some(true)` if the filled area is on the inner side of the
+    /// segment (see [`Winding`]), `Some(false)` if it is on the outer side
+    /// and `None` if the segment isn't on the border of the filled area
+    /// or has zero length.
+    fn border_side(
+        &self,
+        segment: &Segment,
+        fill_rule: FillRule,
+    ) -> Option<bool> {
+        let (point, dir) = SIDE_PARAMS.iter().find_map(|&t| {
+            let dir = segment.direction(t);
+            (dir.magnitude() > f32::EPSILON)
+                .then(|| (segment.point(t), dir.normalize()))
+        })?;
+        let normal = Vector2::new(-dir.y, dir.x) * SIDE_OFFSET;
+
+        let inner = fill_rule.is_filled(self.winding_number(point + normal));
+        let outer = fill_rule.is_filled(self.winding_number(point - normal));
+        match (inner, outer) {
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
 impl Segment {
-    fn get_intersections(&self, other: &Self) -> Vec<Vector2> {
+    /// Returns all intersections with the other segment where `t1` belongs
+    /// to this segment and `t2` to the other one.
+    fn intersections(&self, other: &Self) -> Vec<Intersection> {
         match self {
-            Segment::Line(line) => line.get_intersections(other),
-            Segment::Quadratic(quad) => quad.get_intersections(other),
-            Segment::Cubic(curve) => curve.get_intersections(other),
+            Segment::Line(line) => line.intersections(other),
+            Segment::Quad(quad) => quad.intersections(other),
+            Segment::Curve(curve) => curve.intersections(other),
         }
     }
-}
 
-impl Line {
-    #[inline]
-    fn get_intersections(&self, other: &Segment) -> Vec<Vector2> {
-        let inters = match other {
-            Segment::Line(line) => vec![self.intersects_line(line)],
-            Segment::Quadratic(quad) => self.intersects_quad(quad).to_vec(),
-            Segment::Cubic(curve) => self.intersects_curve(curve).to_vec(),
-        };
-        let intersections = inters.iter().flatten().map(|&inter| inter).collect();
-        intersections
+    /// Returns the same segment going in the opposite direction.
+    fn reversed(&self) -> Self {
+        match *self {
+            Segment::Line(l) => Segment::Line(Line::new(l.to, l.from)),
+            Segment::Quad(q) => Segment::Quad(Quad::new(q.to, q.ctrl, q.from)),
+            Segment::Curve(c) => {
+                Segment::Curve(Curve::new(c.to, c.ctrl2, c.ctrl1, c.from))
+            }
+        }
     }
 
-    #[inline]
-    fn intersects_line(&self, other: &Line) -> Option<Vector2> {
-        crate::math::line_line_intersection(self.from, self.to, other.from, other.to)
+    /// Moves the end points of the segment while keeping control points.
+    fn with_ends(&self, from: Vector2<f32>, to: Vector2<f32>) -> Self {
+        match *self {
+            Segment::Line(_) => Segment::Line(Line::new(from, to)),
+            Segment::Quad(q) => Segment::Quad(Quad::new(from, q.ctrl, to)),
+            Segment::Curve(c) => {
+                Segment::Curve(Curve::new(from, c.ctrl1, c.ctrl2, to))
+            }
+        }
     }
 
-    #[inline]
-    fn intersects_quad(&self, other: &Quad) -> [Option<Vector2>; 2] {
-        crate::math::quad_line_intersection(
-            other.from, other.ctrl, other.to, self.from, self.to,
-        )
+    fn start(&self) -> Vector2<f32> {
+        self.point(0.0)
     }
+}
 
+impl Line {
     #[inline]
-    fn intersects_curve(&self, other: &Curve) -> [Option<Vector2>; 3] {
-        crate::math::cubic_line_intersection(
-            other.from,
-            other.ctrl1,
-            other.ctrl2,
-            other.to,
-            self.from,
-            self.to,
-        )
+    fn intersections(&self, other: &Segment) -> Vec<Intersection> {
+        match other {
+            Segment::Line(line) => math::line_line_intersection(self, line)
+                .into_iter()
+                .collect(),
+            Segment::Quad(quad) => {
+                swapped(&math::quad_line_intersection(quad, self))
+            }
+            Segment::Curve(curve) => {
+                swapped(&math::curve_line_intersection(curve, self))
+            }
+        }
     }
 }
 
 impl Quad {
     #[inline]
-    fn get_intersections(&self, other: &Segment) -> Vec<Vector2> {
-        let inters = match other {
-            Segment::Line(line) => self.intersects_line(line).to_vec(),
-            Segment::Quadratic(quad) => self.intersects_quad(quad).to_vec(),
-            Segment::Cubic(curve) => self.intersects_curve(curve).to_vec(),
-        };
-        let intersections = inters.iter().flatten().map(|&inter| inter).collect();
-        intersections
+    fn intersections(&self, other: &Segment) -> Vec<Intersection> {
+        match other {
+            Segment::Line(line) => {
+                flattened(&math::quad_line_intersection(self, line))
+            }
+            Segment::Quad(quad) => {
+                flattened(&math::quad_quad_intersection(self, quad))
+            }
+            Segment::Curve(curve) => {
+                swapped(&math::curve_quad_intersection(curve, self))
+            }
+        }
     }
+}
 
+impl Curve {
     #[inline]
-    fn intersects_line(&self, other: &Line) -> [Option<Vector2>; 2] {
-        other.intersects_quad(self)
+    fn intersections(&self, other: &Segment) -> Vec<Intersection> {
+        match other {
+            Segment::Line(line) => {
+                flattened(&math::curve_line_intersection(self, line))
+            }
+            Segment::Quad(quad) => {
+                flattened(&math::curve_quad_intersection(self, quad))
+            }
+            Segment::Curve(curve) => {
+                flattened(&math::curve_curve_intersection(self, curve))
+            }
+        }
     }
+}
 
-    #[inline]
-    fn intersects_quad(&self, other: &Quad) -> [Option<Vector2>; 4] {
-        crate::math::quad_quad_intersection(
-            self.from, self.ctrl, self.to, other.from, other.ctrl, self.to,
-        )
-    }
+fn flattened(intersections: &[Option<Intersection>]) -> Vec<Intersection> {
+    intersections.iter().flatten().copied().collect()
+}
 
-    #[inline]
-    fn intersects_curve(&self, other: &Curve) -> [Option<Vector2>; 6] {
-        crate::math::cubic_quad_intersection(
-            other.from,
-            other.ctrl1,
-            other.ctrl2,
-            other.to,
-            self.from,
-            self.ctrl,
-            self.to,
-        )
-    }
+/// Used when the intersection function takes the segments in the
+/// opposite order.
+fn swapped(intersections: &[Option<Intersection>]) -> Vec<Intersection> {
+    intersections
+        .iter()
+        .flatten()
+        .map(|inter| Intersection {
+            point: inter.point,
+            t1: inter.t2,
+            t2: inter.t1,
+        })
+        .collect()
 }
 
-impl Curve {
-    #[inline]
-    fn get_intersections(&self, other: &Segment) -> Vec<Vector2> {
-        let inters = match other {
-            Segment::Line(line) => self.intersects_line(line).to_vec(),
-            Segment::Quadratic(quad) => self.intersects_quad(quad).to_vec(),
-            Segment::Cubic(curve) => self.intersects_curve(curve).to_vec(),
-        };
-        let intersections = inters.iter().flatten().map(|&inter| inter).collect();
-        intersections
+#[inline]
+fn is_inner(t: f32) -> bool {
+    t > END_TOLERANCE && t < 1.0 - END_TOLERANCE
+}
+
+/// Splits the segment at the sorted parameters. Ends of the parts are moved
+/// exactly to the intersection points so they can be connected with parts
+/// of the other intersecting segment.
+fn split_at(segment: Segment, splits: &[(f32, Vector2<f32>)]) -> Vec<Segment> {
+    let mut parts = Vec::with_capacity(splits.len() + 1);
+    let mut rest = segment;
+    let mut rest_t = 0.0;
+    let mut rest_from = segment.start();
+    for &(t, point) in splits {
+        // Map the parameter to the remaining part of the segment.
+        let local_t = (t - rest_t) / (1.0 - rest_t);
+        let (part, next) = rest.split(local_t);
+        parts.push(part.with_ends(rest_from, point));
+        rest = next;
+        rest_t = t;
+        rest_from = point;
     }
+    parts.push(rest.with_ends(rest_from, segment.end()));
+    parts
+}
 
-    #[inline]
-    fn intersects_line(&self, other: &Line) -> [Option<Vector2>; 3] {
-        other.intersects_curve(self)
+/// Connects segments into closed contours by joining the end of each
+/// segment with the closest start of another one.
+///
+/// Returns `None` if a contour can't be closed.
+fn connect(mut segments: Vec<Segment>) -> Option<Vec<Contour>> {
+    let mut contours = Vec::new();
+    while !segments.is_empty() {
+        let first = segments.remove(0);
+        let start = first.start();
+        let mut end = first.end();
+        let mut contour = vec![first];
+
+        while (end - start).magnitude() > JOIN_TOLERANCE {
+            let closest = segments
+                .iter()
+                .enumerate()
+                .map(|(i, segment)| (i, (segment.start() - end).magnitude()))
+                .filter(|(_, dist)| *dist <= JOIN_TOLERANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let (next, _) = closest?;
+            let segment = segments.remove(next);
+            end = segment.end();
+            contour.push(segment);
+        }
+
+        let shoelace = contour
+            .iter()
+            .map(|segment| match segment {
+                Segment::Line(l) => l.shoelace(),
+                Segment::Quad(q) => q.shoelace(),
+                Segment::Curve(c) => c.shoelace(),
+            })
+            .sum::<f32>();
+        contours.push(Contour::new(contour, Winding(shoelace < 0.0)));
     }
+    Some(contours)
+}
 
-    #[inline]
-    fn intersects_quad(&self, other: &Quad) -> [Option<Vector2>; 6] {
-        other.intersects_curve(self)
+#[test]
+fn overlapping_squares_test() {
+    use crate::{font::Offset, path::ShapeBuilder};
+
+    // Two squares overlapping in the middle.
+    let mut builder = ShapeBuilder::new(30, 30, None, Offset::uniform(0.0));
+    for (x, y) in [(4.0, 4.0), (12.0, 12.0)] {
+        builder.open_at(x, y);
+        builder.line_to(x + 14.0, y);
+        builder.line_to(x + 14.0, y + 14.0);
+        builder.line_to(x, y + 14.0);
+        builder.line_to(x, y);
+        builder.close().unwrap();
     }
+    let (mut shape, _) = builder.build().unwrap();
+    let winding_before =
+        |shape: &Shape, x, y| shape.winding_number(Vector2::new(x, y)) != 0;
+    let samples = (0..60)
+        .flat_map(|y| (0..60).map(move |x| (x as f32 * 0.5, y as f32 * 0.5)))
+        .map(|(x, y)| (x + 0.1, y + 0.1))
+        .collect::<Vec<_>>();
+    let filled = samples
+        .iter()
+        .map(|&(x, y)| winding_before(&shape, x, y))
+        .collect::<Vec<_>>();
 
-    #[inline]
-    fn intersects_curve(&self, other: &Curve) -> [Option<Vector2>; 9] {
-        crate::math::cubic_cubic_intersection(
-            self.from,
-            self.ctrl1,
-            self.ctrl2,
-            self.to,
-            other.from,
-            other.ctrl1,
-            other.ctrl2,
-            other.to,
-        )
+    assert!(shape.correct_overlaps(FillRule::NonZero));
+
+    // A single contour outlines the union with 8 segments.
+    assert_eq!(shape.contours.len(), 1);
+    assert_eq!(shape.contours[0].segments.len(), 8);
+    for (&(x, y), &filled) in samples.iter().zip(&filled) {
+        assert_eq!(winding_before(&shape, x, y), filled);
     }
+
+    // The overlapping area is no longer next to any segment.
+    let distance = shape.contours[0].distance(Vector2::new(17.5, 13.0));
+    assert!(distance.real_dist > 0.9);
+    assert!(distance.sign > 0.0);
+
+    // Nothing changes without overlaps.
+    let (min, max) = shape.contours[0].segments[0].bounds();
+    assert!(!shape.correct_overlaps(FillRule::NonZero));
+    assert_eq!(shape.contours[0].segments[0].bounds(), (min, max));
+}
+
+#[test]
+fn open_contour_test() {
+    let line = |x1, y1, x2, y2| {
+        Segment::Line(Line::new(Vector2::new(x1, y1), Vector2::new(x2, y2)))
+    };
+
+    let closed = connect(vec![
+        line(0.0, 0.0, 1.0, 0.0),
+        line(1.0, 1.0, 0.0, 0.0),
+        line(1.0, 0.0, 1.0, 1.0),
+    ])
+    .unwrap();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].segments.len(), 3);
+
+    // The last segment doesn't lead back to the start.
+    let open = vec![line(0.0, 0.0, 1.0, 0.0), line(1.0, 0.0, 1.0, 1.0)];
+    assert!(connect(open).is_none());
+}
+
+#[test]
+fn zero_tangent_test() {
+    use crate::{font::Offset, path::ShapeBuilder};
+
+    // The top edge is a cubic curve which stops in the middle.
+    let mut builder = ShapeBuilder::new(30, 30, None, Offset::uniform(0.0));
+    builder.open_at(4.0, 4.0);
+    builder.curve_to(18.0, 4.0, 4.0, 4.0, 18.0, 4.0);
+    builder.line_to(18.0, 18.0);
+    builder.line_to(4.0, 18.0);
+    builder.line_to(4.0, 4.0);
+    builder.close().unwrap();
+    let (shape, _) = builder.build().unwrap();
+
+    let cusp = shape.contours[0].segments[0];
+    assert_eq!(cusp.direction(0.5).magnitude(), 0.0);
+    let line = Segment::Line(Line::new(cusp.start(), cusp.end()));
+    let side = shape.border_side(&cusp, FillRule::NonZero);
+    assert!(side.is_some());
+    assert_eq!(side, shape.border_side(&line, FillRule::NonZero));
 }
-*/
@@ -1,5 +1,6 @@
 use rusttype::{OutlineBuilder, Scale};
 
+use crate::error::{Error, Result};
 use crate::shape::{Contour, Curve, Line, Quad, Segment, Shape, Winding};
 use crate::vector::Vector2;
 use crate::{BBox, Offset};
@@ -12,6 +13,17 @@ use crate::{BBox, Offset};
 /// - `close`
 ///
 /// After processing all instructions a shape can easily be created.
+///
+/// Instructions don't return errors right away. Instead, the first error
+/// is stored, all following instructions are ignored and the error is
+/// returned by [`PathBuilder::close`] or [`PathBuilder::build_shape`].
+///
+/// Segments with all points at the same position, like duplicate points
+/// in fonts, are skipped. Contours made only of such segments, like the
+/// one-point contours of TrueType glyphs, are dropped.
+///
+/// Contours which don't end at their starting point, like the ones of CFF
+/// outlines, are closed with a line back to it.
 #[derive(Debug)]
 pub struct PathBuilder {
     contours: Vec<Contour>,
@@ -20,14 +32,13 @@ pub struct PathBuilder {
 
     // Temporary values
     shoelace: f32,
+    start_point: Option<Vector2<f32>>,
     last_point: Option<Vector2<f32>>,
     temp_segments: Vec<Segment>,
+    error: Option<Error>,
 }
 
 impl PathBuilder {
-    // TODO add custom errors for the builder
-    // TODO add a function for each step: "error check"
-
     /// Creates a new builder with scale set to `1` meaning none path instructions
     /// will be scaled. Use [`PathBuilder::new_with_scale`] for scaling.
     pub fn new(offset: Offset) -> Self {
@@ -36,23 +47,32 @@ impl PathBuilder {
             offset,
 
             shoelace: 0.0,
+            start_point: None,
             last_point: None,
             temp_segments: Vec::new(),
+            error: None,
         }
     }
 
     pub fn open_at(&mut self, x: f32, y: f32) {
-        self.open_at_check();
+        if let Err(err) = self.open_at_check() {
+            return self.fail(err);
+        }
 
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
+        self.start_point = Some(to);
         self.last_point = Some(to);
     }
 
     pub fn line_to(&mut self, x: f32, y: f32) {
-        self.line_to_check();
-
-        let from = self.last_point.unwrap();
+        let from = match self.segment_check() {
+            Ok(from) => from,
+            Err(err) => return self.fail(err),
+        };
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
+        if from == to {
+            return;
+        }
         let line = Line::new(from, to);
 
         self.shoelace += line.shoelace();
@@ -61,12 +81,16 @@ impl PathBuilder {
     }
 
     pub fn quad_to(&mut self, ctrl_x: f32, ctrl_y: f32, x: f32, y: f32) {
-        self.quad_to_check();
-
-        let from = self.last_point.unwrap();
+        let from = match self.segment_check() {
+            Ok(from) => from,
+            Err(err) => return self.fail(err),
+        };
         let control =
             Vector2::new(ctrl_x + self.offset.x, ctrl_y + self.offset.y);
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
+        if from == control && from == to {
+            return;
+        }
         let quad = Quad::new(from, control, to);
 
         self.shoelace += quad.shoelace();
@@ -84,25 +108,52 @@ impl PathBuilder {
         x: f32,
         y: f32,
     ) {
-        self.curve_to_check();
-
-        let from = self.last_point.unwrap();
+        let from = match self.segment_check() {
+            Ok(from) => from,
+            Err(err) => return self.fail(err),
+        };
         let ctrl1 =
             Vector2::new(ctrl1_x + self.offset.x, ctrl1_y + self.offset.y);
         let ctrl2 =
             Vector2::new(ctrl2_x + self.offset.x, ctrl2_y + self.offset.y);
         let to = Vector2::new(x + self.offset.x, y + self.offset.y);
+        if from == ctrl1 && from == ctrl2 && from == to {
+            return;
+        }
         let curve = Curve::new(from, ctrl1, ctrl2, to);
 
         self.shoelace += curve.shoelace();
         self.temp_segments
             .push(Segment::Curve(Curve::new(from, ctrl1, ctrl2, to)));
         self.last_point = Some(to);
-        unimplemented!("Not implemented!!!")
     }
 
-    pub fn close(&mut self) {
-        self.close_check();
+    /// Closes the current contour, adding a line back to its starting
+    /// point if the last segment doesn't end there. Contours without any
+    /// segments are dropped.
+    ///
+    /// Returns the first error which occurred since the builder
+    /// has been created.
+    pub fn close(&mut self) -> Result<()> {
+        if let Err(err) = self.close_check() {
+            self.fail(err.clone());
+            return Err(err);
+        }
+
+        if let (Some(last), Some(start)) = (self.last_point, self.start_point) {
+            if last != start {
+                let line = Line::new(last, start);
+                self.shoelace += line.shoelace();
+                self.temp_segments.push(Segment::Line(line));
+            }
+        }
+
+        if self.temp_segments.is_empty() {
+            self.shoelace = 0.0;
+            self.start_point = None;
+            self.last_point = None;
+            return Ok(());
+        }
 
         // TODO test if windings are right
         let winding = Winding(self.shoelace < 0.0);
@@ -111,71 +162,66 @@ impl PathBuilder {
 
         self.contours.push(Contour::new(segments, winding));
         self.shoelace = 0.0;
+        self.start_point = None;
         self.last_point = None;
+
+        Ok(())
     }
 
+    /// Stores the error if it's the first one.
     #[inline]
-    fn open_at_check(&self) {
-        assert!(
-            self.last_point.is_none(),
-            "PathBuilder Error: The last contour has not been closed!"
-        );
-        assert!(
-            self.temp_segments.is_empty(),
-            "PathBuilder Error: The last contour wasn't closed!"
-        );
+    fn fail(&mut self, err: Error) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
     }
 
     #[inline]
-    fn line_to_check(&self) {
-        assert!(
-            self.last_point.is_some(),
-            "PathBuilder Error: Open a new contour before adding segments!"
-        );
+    fn error_check(&self) -> Result<()> {
+        match &self.error {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
+        }
     }
 
     #[inline]
-    fn quad_to_check(&self) {
-        assert!(
-            self.last_point.is_some(),
-            "PathBuilder Error: Open a new contour before adding segments!"
-        );
+    fn open_at_check(&self) -> Result<()> {
+        self.error_check()?;
+        if self.last_point.is_some() || !self.temp_segments.is_empty() {
+            return Err(Error::UnclosedContour);
+        }
+        Ok(())
     }
 
+    /// Returns the last point which is the starting point of the new segment.
     #[inline]
-    fn curve_to_check(&self) {
-        assert!(
-            self.last_point.is_some(),
-            "PathBuilder Error: Open a new contour before adding segments!"
-        );
+    fn segment_check(&self) -> Result<Vector2<f32>> {
+        self.error_check()?;
+        self.last_point.ok_or(Error::NoOpenContour)
     }
 
     #[inline]
-    fn close_check(&self) {
-        assert!(
-            !self.temp_segments.is_empty(),
-            "PathBuilder Error: There are no contours to close or there are zero segments!"
-        );
-        assert!(
-            self.last_point.is_some(),
-            "PathBuilder Error: The last contour has already been closed!"
-        );
+    fn close_check(&self) -> Result<()> {
+        self.error_check()?;
+        if self.last_point.is_none() {
+            return Err(Error::NoOpenContour);
+        }
+        Ok(())
     }
 
-    pub fn build_shape(self) -> Shape {
-        assert!(
-            !self.contours.is_empty(),
-            "PathBuilder Error: There are no contours."
-        );
-        assert!(
-            self.last_point.is_none(),
-            "PathBuilder Error: The last contour is still open."
-        );
+    pub fn build_shape(self) -> Result<Shape> {
+        self.error_check()?;
+        if self.last_point.is_some() {
+            return Err(Error::UnclosedContour);
+        }
+        if self.contours.is_empty() {
+            return Err(Error::EmptyShape);
+        }
 
-        Shape::new(self.contours)
+        Ok(Shape::new(self.contours))
     }
 
-    pub fn build_shape_scaled(mut self, scale: Scale) -> Shape {
+    pub fn build_shape_scaled(mut self, scale: Scale) -> Result<Shape> {
         for contour in self.contours.iter_mut() {
             for segment in contour.segments.iter_mut() {
                 match segment {
@@ -192,8 +238,6 @@ impl PathBuilder {
 
 impl OutlineBuilder for PathBuilder {
     fn move_to(&mut self, x: f32, y: f32) {
-        println!("moving to: {} {}", x, y);
-
         self.open_at(x, y);
     }
 
@@ -228,10 +272,23 @@ impl OutlineBuilder for PathBuilder {
     fn close(&mut self) {
         //println!("_________END_________");
 
-        self.close();
+        // The error is stored and returned by `build_shape`.
+        let _ = self.close();
     }
 }
 
+/// Builds a custom shape for [`GlyphOutline::from_shape`].
+///
+/// Coordinates are in pixels of the generated bitmap (before the optional
+/// scale) with `x` increasing to the right and `y` increasing downwards,
+/// same as glyph outlines of fonts. Pixel `(0, 0)` is the first pixel of
+/// the first row and `width` and `height` set the [`BBox`] from `(0, 0)`.
+///
+/// Shapes drawn with `y` increasing upwards come out upside down. Generate
+/// them with [`YAxis::Up`](crate::YAxis::Up) to get rows going from the
+/// bottom of the shape instead.
+///
+/// [`GlyphOutline::from_shape`]: crate::GlyphOutline::from_shape
 pub struct ShapeBuilder {
     path: PathBuilder,
     bbox: BBox,
@@ -281,18 +338,115 @@ impl ShapeBuilder {
         self.path.curve_to(ctrl1_x, ctrl1_y, ctrl2_x, ctrl2_y, x, y);
     }
 
-    pub fn close(&mut self) {
-        self.path.close();
+    pub fn close(&mut self) -> Result<()> {
+        self.path.close()
     }
 
-    pub fn build(mut self) -> (Shape, BBox) {
+    pub fn build(mut self) -> Result<(Shape, BBox)> {
         if let Some(scale) = self.scale {
             self.bbox.scale(scale);
-            (self.path.build_shape_scaled(scale), self.bbox)
+            Ok((self.path.build_shape_scaled(scale)?, self.bbox))
         } else {
-            (self.path.build_shape(), self.bbox)
+            Ok((self.path.build_shape()?, self.bbox))
         }
     }
 }
 
 // TODO maybe add tests for each module
+
+#[test]
+fn path_errors_test() {
+    let offset = Offset::uniform(0.0);
+
+    let mut builder = PathBuilder::new(offset);
+    builder.line_to(1.0, 1.0);
+    assert_eq!(builder.close(), Err(Error::NoOpenContour));
+
+    // Empty contours are dropped, a shape of only those is empty.
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(0.0, 0.0);
+    assert_eq!(builder.close(), Ok(()));
+    assert_eq!(builder.build_shape().unwrap_err(), Error::EmptyShape);
+
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(0.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    builder.open_at(5.0, 5.0);
+    // The first error is kept even if the following instructions are valid.
+    builder.line_to(1.0, 1.0);
+    assert_eq!(builder.close(), Err(Error::UnclosedContour));
+    assert_eq!(builder.build_shape().unwrap_err(), Error::UnclosedContour);
+
+    // Segments without length are skipped.
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(0.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    builder.quad_to(1.0, 0.0, 1.0, 0.0);
+    builder.curve_to(1.0, 0.0, 1.0, 0.0, 1.0, 0.0);
+    builder.line_to(0.0, 1.0);
+    builder.line_to(0.0, 0.0);
+    assert_eq!(builder.close(), Ok(()));
+    let shape = builder.build_shape().unwrap();
+    assert_eq!(shape.contours[0].segments.len(), 3);
+
+    // One-point contours come as a line to the starting point.
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(5.0, 5.0);
+    builder.line_to(5.0, 5.0);
+    assert_eq!(builder.close(), Ok(()));
+    builder.open_at(0.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    builder.line_to(0.0, 1.0);
+    assert_eq!(builder.close(), Ok(()));
+    builder.open_at(3.0, 3.0);
+    assert_eq!(builder.close(), Ok(()));
+    let shape = builder.build_shape().unwrap();
+    assert_eq!(shape.contours.len(), 1);
+    assert_eq!(shape.contours[0].segments.len(), 3);
+
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(0.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    assert_eq!(builder.build_shape().unwrap_err(), Error::UnclosedContour);
+
+    let builder = PathBuilder::new(offset);
+    assert_eq!(builder.build_shape().unwrap_err(), Error::EmptyShape);
+
+    let mut builder = PathBuilder::new(offset);
+    builder.open_at(0.0, 0.0);
+    builder.line_to(1.0, 0.0);
+    builder.line_to(0.0, 1.0);
+    assert_eq!(builder.close(), Ok(()));
+    assert!(builder.build_shape().is_ok());
+}
+
+#[test]
+fn implicit_close_test() {
+    let offset = Offset::uniform(0.0);
+    let shape = |close_explicitly: bool| {
+        let mut builder = PathBuilder::new(offset);
+        builder.open_at(1.0, 1.0);
+        builder.line_to(9.0, 1.0);
+        builder.quad_to(9.0, 9.0, 1.0, 9.0);
+        if close_explicitly {
+            builder.line_to(1.0, 1.0);
+        }
+        builder.close().unwrap();
+        builder.build_shape().unwrap()
+    };
+    let open = shape(false);
+    let closed = shape(true);
+
+    let (open, closed) = (&open.contours[0], &closed.contours[0]);
+    assert_eq!(open.segments.len(), closed.segments.len());
+    assert_eq!(open.winding(), closed.winding());
+    for point in [(0.0, 5.0), (2.0, 5.0), (5.0, 5.0), (12.0, 3.0)] {
+        let point = Vector2::new(point.0, point.1);
+        let (a, b) = (open.distance(point), closed.distance(point));
+        assert_eq!(a.real_signed(), b.real_signed());
+    }
+    // The closing line is the closest segment left of the contour.
+    let distance = open.distance(Vector2::new(0.0, 5.0));
+    assert!((distance.real_dist - 1.0).abs() < 1e-5);
+}
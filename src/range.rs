@@ -0,0 +1,139 @@
+/// Unit of a [`Range`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RangeUnit {
+    /// Pixels of the generated bitmap.
+    #[default]
+    Pixels,
+    /// Size of the em square, converted to pixels with the `units_per_em`
+    /// of the font and the scale the glyph was built with.
+    Ems,
+}
+
+/// Range of signed distances which get mapped to the output values.
+///
+/// Distances equal to `lower` (outside of the shape) map to the lowest
+/// value and distances equal to `upper` (inside of the shape) to the
+/// highest one. Distances out of the range get clamped.
+///
+/// Integers and floats convert into symmetric ranges in pixels.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Range {
+    pub lower: f32,
+    pub upper: f32,
+    pub unit: RangeUnit,
+}
+
+impl Range {
+    /// Symmetric range of the provided width in pixels.
+    pub fn pixels(width: f32) -> Self {
+        Self {
+            lower: -0.5 * width,
+            upper: 0.5 * width,
+            unit: RangeUnit::Pixels,
+        }
+    }
+
+    /// Symmetric range of the provided width in ems.
+    pub fn ems(width: f32) -> Self {
+        Self {
+            unit: RangeUnit::Ems,
+            ..Self::pixels(width)
+        }
+    }
+
+    /// Range which reaches `outer` far outside of the shape and `inner`
+    /// far inside of it.
+    pub fn asymmetric(outer: f32, inner: f32, unit: RangeUnit) -> Self {
+        Self {
+            lower: -outer,
+            upper: inner,
+            unit,
+        }
+    }
+
+    /// Returns the distance between the range bounds.
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.upper - self.lower
+    }
+
+    /// Returns the distance in the middle of the range which gets mapped
+    /// to the middle value.
+    #[inline]
+    pub fn middle(&self) -> f32 {
+        0.5 * (self.lower + self.upper)
+    }
+
+    /// Returns the same range in pixels. Ranges in ems get multiplied by
+    /// `pixels_per_em`.
+    pub fn to_pixels(self, pixels_per_em: f32) -> Self {
+        match self.unit {
+            RangeUnit::Pixels => self,
+            RangeUnit::Ems => Self {
+                lower: self.lower * pixels_per_em,
+                upper: self.upper * pixels_per_em,
+                unit: RangeUnit::Pixels,
+            },
+        }
+    }
+
+    /// Maps the signed distance from the range to `[0, 1]` without
+    /// clamping.
+    ///
+    /// The range has to be in pixels.
+    #[inline]
+    pub(crate) fn normalize(&self, signed_distance: f32) -> f32 {
+        (signed_distance - self.middle()) / self.width() + 0.5
+    }
+}
+
+impl From<usize> for Range {
+    fn from(width: usize) -> Self {
+        Self::pixels(width as f32)
+    }
+}
+
+impl From<f32> for Range {
+    fn from(width: f32) -> Self {
+        Self::pixels(width)
+    }
+}
+
+#[test]
+fn range_test() {
+    let range = Range::from(4);
+    assert_eq!(range.width(), 4.0);
+    assert_eq!(range.normalize(0.0), 0.5);
+    assert_eq!(range.normalize(-2.0), 0.0);
+    assert_eq!(range.normalize(1.0), 0.75);
+
+    let em = Range::ems(0.125).to_pixels(32.0);
+    assert_eq!(em, Range::pixels(4.0));
+
+    // Mostly inside of the shape.
+    let range = Range::asymmetric(1.0, 3.0, RangeUnit::Pixels);
+    assert_eq!(range.middle(), 1.0);
+    assert_eq!(range.normalize(-1.0), 0.0);
+    assert_eq!(range.normalize(3.0), 1.0);
+}
+
+#[test]
+fn em_range_test() {
+    use crate::font::{Font, Offset};
+    use rusttype::Scale;
+
+    let data = include_bytes!("../examples/fonts/monserat.ttf");
+    let font = Font::from_slice(data).unwrap();
+    let outline = font
+        .glyph('k')
+        .build(Scale::uniform(40.0), Offset::uniform(2.0))
+        .unwrap();
+    let pixels_per_em = font.pixels_per_em(Scale::uniform(40.0));
+    assert_eq!(outline.pixels_per_em(), pixels_per_em);
+
+    let em = outline.generate_sdf(Range::ems(0.1));
+    let pixels = outline.generate_sdf(0.1 * pixels_per_em);
+    assert_eq!(em, pixels);
+}
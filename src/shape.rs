@@ -2,9 +2,14 @@ use std::slice::Iter;
 
 use rusttype::Scale;
 
-use crate::{font::BBox, math::Distance, vector::Vector2};
-
-#[derive(Debug)]
+use crate::{
+    coloring::EdgeColor,
+    font::BBox,
+    math::{self, Distance},
+    vector::Vector2,
+};
+
+#[derive(Debug, Clone)]
 pub struct Shape {
     pub contours: Vec<Contour>,
 }
@@ -15,6 +20,29 @@ impl Shape {
         Self { contours }
     }
 
+    /// Returns the winding number of the shape around the point.
+    ///
+    /// The point is inside of the shape filled by the non-zero rule if the
+    /// winding number isn't zero, or by the even-odd rule if it is odd.
+    pub fn winding_number(&self, point: Vector2<f32>) -> i32 {
+        self.contours
+            .iter()
+            .flat_map(|contour| contour.iter())
+            .map(|segment| segment.crossings(point))
+            .sum()
+    }
+
+    /// Returns all points where the contours cross the horizontal line at
+    /// `y`, sorted by `x`. See [`Segment::scanline_crossings`].
+    pub fn scanline(&self, y: f32) -> Vec<(f32, i32)> {
+        let mut crossings = Vec::new();
+        for segment in self.contours.iter().flat_map(|contour| contour.iter()) {
+            segment.scanline_crossings(y, &mut crossings);
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        crossings
+    }
+
     /// Returns a bounding box which is created paying attention to
     /// line and curve points instead of their bodies.
     /// TODO: maybe not needed
@@ -79,15 +107,46 @@ impl Shape {
     }
 }
 
-#[derive(Debug)]
+/// Rule which decides if a point is inside of the shape based on its
+/// winding number, the number of times the contours go around the point.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FillRule {
+    /// Points with a non-zero winding number are inside.
+    #[default]
+    NonZero,
+    /// Points with an odd winding number are inside.
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Checks if a point with the provided winding number is inside.
+    #[inline]
+    pub fn is_filled(self, winding_number: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Contour {
     pub segments: Vec<Segment>,
+    /// Edge color of each segment, indexed the same as `segments`.
+    pub(crate) colors: Vec<EdgeColor>,
     winding: Winding,
 }
 
 impl Contour {
+    /// Creates a new contour with all segments colored white.
     pub fn new(segments: Vec<Segment>, winding: Winding) -> Self {
-        Self { segments, winding }
+        let colors = vec![EdgeColor::White; segments.len()];
+        Self {
+            segments,
+            colors,
+            winding,
+        }
     }
 
     /// Returns the [`Distance`] to the provided point.
@@ -111,13 +170,27 @@ impl Contour {
         self.segments.iter()
     }
 
+    /// Iterates over segments paired with their edge colors.
+    #[inline]
+    pub fn iter_colored(
+        &self,
+    ) -> impl Iterator<Item = (&Segment, EdgeColor)> + '_ {
+        self.segments.iter().zip(self.colors.iter().copied())
+    }
+
+    /// Returns the edge colors of all segments.
+    #[inline]
+    pub fn colors(&self) -> &[EdgeColor] {
+        &self.colors
+    }
+
     #[inline]
     pub fn winding(&self) -> Winding {
         self.winding
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Segment {
     Line(Line),
     Quad(Quad),
@@ -125,20 +198,186 @@ pub enum Segment {
 }
 
 impl Segment {
-    fn distance(&self, point: Vector2<f32>) -> Distance {
+    pub fn distance(&self, point: Vector2<f32>) -> Distance {
         match self {
             Segment::Line(l) => l.calculate_distance(point),
             Segment::Quad(q) => q.calculate_distance(point),
             Segment::Curve(c) => c.calculate_distance(point),
         }
     }
+
+    /// Returns the point of the segment at `t`.
+    pub fn point(&self, t: f32) -> Vector2<f32> {
+        match self {
+            Segment::Line(l) => math::line_fn(l.from, l.to, t),
+            Segment::Quad(q) => math::quadratic_fn(q.from, q.ctrl, q.to, t),
+            Segment::Curve(c) => {
+                math::cubic_fn(c.from, c.ctrl1, c.ctrl2, c.to, t)
+            }
+        }
+    }
+
+    /// Returns the direction (tangent) of the segment at `t`.
+    pub fn direction(&self, t: f32) -> Vector2<f32> {
+        match self {
+            Segment::Line(l) => l.direction(),
+            Segment::Quad(q) => q.direction(t),
+            Segment::Curve(c) => c.direction(t),
+        }
+    }
+
+    /// Returns the signed number of times the segment crosses the ray
+    /// going from the point in the positive `x` direction. Crossings where
+    /// `y` increases are positive.
+    pub fn crossings(&self, point: Vector2<f32>) -> i32 {
+        if point.x >= self.bounds().1.x {
+            return 0;
+        }
+        let mut crossings = Vec::new();
+        self.scanline_crossings(point.y, &mut crossings);
+        crossings
+            .into_iter()
+            .filter(|(x, _)| *x > point.x)
+            .map(|(_, direction)| direction)
+            .sum()
+    }
+
+    /// Pushes the `x` coordinate of every point where the segment crosses
+    /// the horizontal line at `y`, together with the crossing direction
+    /// (`1` if `y` increases, `-1` otherwise).
+    ///
+    /// The segment is split into parts which are monotonic in `y`. Each part
+    /// includes its starting point but not its ending point, so a line going
+    /// through a point where two parts meet is counted only once.
+    pub fn scanline_crossings(&self, y: f32, crossings: &mut Vec<(f32, i32)>) {
+        let (min, max) = self.bounds();
+        if y < min.y || y > max.y {
+            return;
+        }
+
+        // Parameters where `y` stops increasing or decreasing.
+        let mut splits = match self {
+            Segment::Line(_) => vec![],
+            Segment::Quad(q) => {
+                let a = q.from.y - 2.0 * q.ctrl.y + q.to.y;
+                vec![(q.from.y - q.ctrl.y) / a]
+            }
+            Segment::Curve(c) => {
+                let a = 3.0 * (c.ctrl1.y - c.ctrl2.y) + c.to.y - c.from.y;
+                let b = 2.0 * (c.from.y - 2.0 * c.ctrl1.y + c.ctrl2.y);
+                let c = c.ctrl1.y - c.from.y;
+                math::quadratic_roots(a, b, c)
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+        };
+        splits.retain(|t| *t > 0.0 && *t < 1.0);
+        splits.sort_by(f32::total_cmp);
+        splits.insert(0, 0.0);
+        splits.push(1.0);
+
+        // Exact end points keep the counting consistent between segments.
+        let point_at = |t: f32| {
+            if t == 1.0 {
+                self.end()
+            } else {
+                self.point(t)
+            }
+        };
+
+        for part in splits.windows(2) {
+            let (mut t0, mut t1) = (part[0], part[1]);
+            let (y0, y1) = (point_at(t0).y, point_at(t1).y);
+            if (y0 <= y) == (y1 <= y) {
+                continue;
+            }
+
+            // Bisect the monotonic part to find where it crosses the line.
+            for _ in 0..24 {
+                let mid = 0.5 * (t0 + t1);
+                if (point_at(mid).y <= y) == (y0 <= y) {
+                    t0 = mid;
+                } else {
+                    t1 = mid;
+                }
+            }
+            let x = point_at(0.5 * (t0 + t1)).x;
+            crossings.push((x, if y1 > y0 { 1 } else { -1 }));
+        }
+    }
+
+    /// Returns the ending point of the segment.
+    pub fn end(&self) -> Vector2<f32> {
+        match self {
+            Segment::Line(l) => l.to,
+            Segment::Quad(q) => q.to,
+            Segment::Curve(c) => c.to,
+        }
+    }
+
+    /// Splits the segment at `t` into two segments.
+    pub fn split(&self, t: f32) -> (Segment, Segment) {
+        match self {
+            Segment::Line(l) => {
+                let (first, second) = l.split(t);
+                (Segment::Line(first), Segment::Line(second))
+            }
+            Segment::Quad(q) => {
+                let (first, second) = q.split(t);
+                (Segment::Quad(first), Segment::Quad(second))
+            }
+            Segment::Curve(c) => {
+                let (first, second) = c.split(t);
+                (Segment::Curve(first), Segment::Curve(second))
+            }
+        }
+    }
+
+    /// Returns the top left and bottom right corner of the bounding box
+    /// of the segment's points. Curves always lie inside of it.
+    pub fn bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        let points = match self {
+            Segment::Line(l) => vec![l.from, l.to],
+            Segment::Quad(q) => vec![q.from, q.ctrl, q.to],
+            Segment::Curve(c) => vec![c.from, c.ctrl1, c.ctrl2, c.to],
+        };
+        let mut min = points[0];
+        let mut max = points[0];
+        for p in points {
+            min = Vector2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vector2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        (min, max)
+    }
+
+    /// Splits the segment into three segments of equal parameter length.
+    pub fn split_in_thirds(&self) -> [Segment; 3] {
+        match self {
+            Segment::Line(l) => {
+                let (first, rest) = l.split(1.0 / 3.0);
+                let (second, third) = rest.split(0.5);
+                [first, second, third].map(Segment::Line)
+            }
+            Segment::Quad(q) => {
+                let (first, rest) = q.split(1.0 / 3.0);
+                let (second, third) = rest.split(0.5);
+                [first, second, third].map(Segment::Quad)
+            }
+            Segment::Curve(c) => {
+                let (first, rest) = c.split(1.0 / 3.0);
+                let (second, third) = rest.split(0.5);
+                [first, second, third].map(Segment::Curve)
+            }
+        }
+    }
 }
 
 /// Represents a parametric line with constants:
 ///
 /// - `from` - starting point
 /// - `to` - ending point
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Line {
     pub from: Vector2<f32>,
     pub to: Vector2<f32>,
@@ -159,6 +398,17 @@ impl Line {
         crate::math::line_signed_distance(self, point)
     }
 
+    #[inline]
+    pub fn direction(&self) -> Vector2<f32> {
+        self.to - self.from
+    }
+
+    /// Splits the line at `t` into two lines.
+    pub fn split(&self, t: f32) -> (Line, Line) {
+        let mid = math::line_fn(self.from, self.to, t);
+        (Line::new(self.from, mid), Line::new(mid, self.to))
+    }
+
     // TODO explain
     #[inline]
     pub fn shoelace(&self) -> f32 {
@@ -171,7 +421,7 @@ impl Line {
 /// - `from` - starting point
 /// - `ctrl` - control point
 /// - `to` - ending point
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Quad {
     pub from: Vector2<f32>,
     pub ctrl: Vector2<f32>,
@@ -197,6 +447,28 @@ impl Quad {
         crate::math::quad_signed_distance(self, point)
     }
 
+    /// Returns the direction at `t`. In case the control point overlaps
+    /// an end point the direction of the line `from`-`to` is returned.
+    pub fn direction(&self, t: f32) -> Vector2<f32> {
+        let dir =
+            math::quadratic_derivative_fn(self.from, self.ctrl, self.to, t);
+        if dir.is_zero() {
+            return self.to - self.from;
+        }
+        dir
+    }
+
+    /// Splits the curve at `t` into two curves using de Casteljau's algorithm.
+    pub fn split(&self, t: f32) -> (Quad, Quad) {
+        let ctrl1 = math::line_fn(self.from, self.ctrl, t);
+        let ctrl2 = math::line_fn(self.ctrl, self.to, t);
+        let mid = math::line_fn(ctrl1, ctrl2, t);
+        (
+            Quad::new(self.from, ctrl1, mid),
+            Quad::new(mid, ctrl2, self.to),
+        )
+    }
+
     // TODO explain
     #[inline]
     pub fn shoelace(&self) -> f32 {
@@ -210,7 +482,7 @@ impl Quad {
 /// - `ctrl1` - control point
 /// - `ctrl2` - control point
 /// - `to` - ending point
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Curve {
     pub from: Vector2<f32>,
     pub ctrl1: Vector2<f32>,
@@ -245,6 +517,38 @@ impl Curve {
         crate::math::curve_signed_distance(self, point)
     }
 
+    /// Returns the direction at `t`. In case a control point overlaps
+    /// the neighboring end point the direction towards the other
+    /// control point is returned.
+    pub fn direction(&self, t: f32) -> Vector2<f32> {
+        let dir = math::cubic_derivative_fn(
+            self.from, self.ctrl1, self.ctrl2, self.to, t,
+        );
+        if dir.is_zero() {
+            if t == 0.0 {
+                return self.ctrl2 - self.from;
+            }
+            if t == 1.0 {
+                return self.to - self.ctrl1;
+            }
+        }
+        dir
+    }
+
+    /// Splits the curve at `t` into two curves using de Casteljau's algorithm.
+    pub fn split(&self, t: f32) -> (Curve, Curve) {
+        let a = math::line_fn(self.from, self.ctrl1, t);
+        let b = math::line_fn(self.ctrl1, self.ctrl2, t);
+        let c = math::line_fn(self.ctrl2, self.to, t);
+        let ab = math::line_fn(a, b, t);
+        let bc = math::line_fn(b, c, t);
+        let mid = math::line_fn(ab, bc, t);
+        (
+            Curve::new(self.from, a, ab, mid),
+            Curve::new(mid, bc, c, self.to),
+        )
+    }
+
     // TODO explain
     #[inline]
     pub fn shoelace(&self) -> f32 {